@@ -6,19 +6,16 @@ use EasyAlgolia::{
     Index,
 };
 
-#[derive(serde::Serialize, Default)]
+// the name of the game is used as object id
+#[derive(serde::Serialize, Default, AlgoliaObject)]
 struct Game {
+    #[algolia(object_id)]
     pub name: String,
     genre: String,
     platform: String,
     release_year: u32,
     is_multiplayer: bool,
 }
-impl AlgoliaObject for Game {
-    fn get_object_id(&self) -> String {
-        String::from(&self.name)
-    }
-}
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), EasyAlgoliaError> {
     dotenv().ok();
@@ -29,12 +26,14 @@ async fn main() -> Result<(), EasyAlgoliaError> {
         ..Default::default()
     };
     // if document is not present in the index, this will insert a new document
-    let _ = client.put_document_async(&my_index, &doc).await?;
+    let response = client.put_document_async(&my_index, &doc).await?;
+    // writes are applied asynchronously, wait until the document is searchable
+    client.wait_task_async(&my_index, response.task_id).await?;
     // calling the same function again with same document, ie doc.get_object_id() = "LastOfUs" will
     // update the docmuent
-    let _ = client.put_document_async(&my_index, &doc).await?;
+    client.put_document_async(&my_index, &doc).await?;
     // same , the document can be deleted as
     // doucment delete method consumes the document
-    let _ = client.delete_document_async(&my_index, doc).await?;
+    client.delete_document_async(&my_index, doc).await?;
     Ok(())
 }
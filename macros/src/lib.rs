@@ -1,15 +1,393 @@
 //! Macros for AlgoliaObject
 
+mod case;
+
+use case::{
+    pascal_case,
+    RenameRule,
+};
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{
+    format_ident,
+    quote,
+    ToTokens,
+};
 use syn::{
+    ext::IdentExt,
+    meta::ParseNestedMeta,
     parse_macro_input,
+    spanned::Spanned,
+    Attribute,
+    Data,
     DeriveInput,
+    Expr,
+    Field,
+    Fields,
+    LitStr,
+    Member,
 };
 
-#[proc_macro_derive(AlgoliaObject)]
-pub fn my_macro(input: TokenStream) -> TokenStream {
-    let input: DeriveInput = syn::parse(input).unwrap();
+/// derive `EasyAlgolia::algoliaobject::AlgoliaObject`
+/// the object id is the field marked `#[algolia(object_id)]`, or the expression given with
+/// `#[algolia(object_id = "expr")]` on the type, Algolia generates one when neither is set
+/// structs with named fields also get a const per attribute name and a `{Struct}Field` enum,
+/// names follow serde renames, `#[algolia(rename = "name")]` overrides them and
+/// `#[algolia(skip)]` leaves the field out
+#[proc_macro_derive(AlgoliaObject, attributes(algolia))]
+pub fn derive_algolia_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// where the object id of a document comes from
+enum ObjectId {
+    /// `#[algolia(object_id)]` on a field
+    Field(Member),
+    /// `#[algolia(object_id = "expr")]` on the type
+    Expr(Expr),
+}
+
+impl ObjectId {
+    /// expression of the object id, evaluated with `self` in scope
+    fn expr(&self) -> TokenStream2 {
+        match self {
+            Self::Field(member) => quote!(self.#member),
+            Self::Expr(expr) => expr.to_token_stream(),
+        }
+    }
+}
+
+/// keeps track of the object id marker, a document has at most one
+#[derive(Default)]
+struct ObjectIdMarker {
+    object_id: Option<ObjectId>,
+}
+
+impl ObjectIdMarker {
+    fn set(&mut self, object_id: ObjectId, span: proc_macro2::Span) -> syn::Result<()> {
+        if self.object_id.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "the object id is already set, only one #[algolia(object_id)] is allowed",
+            ));
+        }
+        self.object_id = Some(object_id);
+        Ok(())
+    }
+}
+
+const OBJECT_ID_USAGE: &str =
+    "use #[algolia(object_id)] on a field or #[algolia(object_id = \"expr\")] on the type";
+
+/// consume the value of a serde attribute the derive does not care about
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta(&nested))?;
+    }
+    Ok(())
+}
+
+/// value of `rename = "name"` or `rename(serialize = "name")`, the serialized name is the one
+/// stored in Algolia
+fn serialized_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            name = Some(nested.value()?.parse()?);
+            return Ok(());
+        }
+        skip_meta(&nested)
+    })?;
+    Ok(name)
+}
+
+/// `#[algolia(object_id = "expr")]` and serde `rename_all` on the type
+fn parse_container_attributes(
+    attrs: &[Attribute],
+    marker: &mut ObjectIdMarker,
+) -> syn::Result<Option<RenameRule>> {
+    let mut rename_all = None;
+    for attr in attrs {
+        if attr.path().is_ident("algolia") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("object_id") {
+                    let expr: LitStr = meta
+                        .value()
+                        .map_err(|_| meta.error(OBJECT_ID_USAGE))?
+                        .parse()?;
+                    return marker.set(ObjectId::Expr(expr.parse()?), expr.span());
+                }
+                Err(meta.error("unsupported algolia attribute"))
+            })?;
+        } else if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    if let Some(rule) = serialized_name(&meta)? {
+                        rename_all = Some(RenameRule::parse(&rule)?);
+                    }
+                    return Ok(());
+                }
+                skip_meta(&meta)
+            })?;
+        }
+    }
+    Ok(rename_all)
+}
+
+/// what the derive reads from the attributes of a field
+#[derive(Default)]
+struct FieldAttributes {
+    /// `#[algolia(rename = "name")]`
+    rename: Option<LitStr>,
+    /// `#[serde(rename = "name")]`
+    serde_rename: Option<LitStr>,
+    /// `#[algolia(skip)]`, or a field serde does not serialize as an attribute
+    skip: bool,
+}
+
+/// `#[algolia(object_id)]`, `#[algolia(rename = "name")]`, `#[algolia(skip)]` and the serde
+/// attributes changing the attribute name of a field
+fn parse_field_attributes(
+    field: &Field,
+    member: &Member,
+    marker: &mut ObjectIdMarker,
+) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
+    for attr in &field.attrs {
+        if attr.path().is_ident("algolia") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("object_id") {
+                    if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+                        return Err(meta.error(OBJECT_ID_USAGE));
+                    }
+                    return marker.set(ObjectId::Field(member.clone()), meta.path.span());
+                }
+                if meta.path.is_ident("rename") || meta.path.is_ident("skip") {
+                    // tuple structs are serialized as arrays, their fields have no name
+                    if let Member::Unnamed(_) = member {
+                        return Err(meta.error(
+                            "#[algolia(rename)] and #[algolia(skip)] are only supported on \
+                             named fields",
+                        ));
+                    }
+                }
+                if meta.path.is_ident("rename") {
+                    attributes.rename = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("skip") {
+                    attributes.skip = true;
+                    return Ok(());
+                }
+                Err(meta.error("unsupported algolia attribute"))
+            })?;
+        } else if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    attributes.serde_rename = serialized_name(&meta)?;
+                    return Ok(());
+                }
+                // flattened fields bring the attributes of the inner type, not their own
+                if ["skip", "skip_serializing", "flatten"]
+                    .iter()
+                    .any(|skip| meta.path.is_ident(skip))
+                {
+                    attributes.skip = true;
+                }
+                skip_meta(&meta)
+            })?;
+        }
+    }
+    Ok(attributes)
+}
+
+/// attribute of a document, as stored in Algolia
+struct AttributeField {
+    /// name of the const, such as `RELEASE_YEAR`
+    constant: syn::Ident,
+    /// variant of the field enum, such as `ReleaseYear`
+    variant: syn::Ident,
+    /// attribute name in Algolia, such as `releaseYear`
+    name: String,
+}
+
+impl AttributeField {
+    fn new(
+        ident: &syn::Ident,
+        attributes: FieldAttributes,
+        rename_all: Option<RenameRule>,
+    ) -> Self {
+        let field = ident.unraw().to_string();
+        let name = match (attributes.rename, attributes.serde_rename, rename_all) {
+            (Some(rename), _, _) | (None, Some(rename), _) => rename.value(),
+            (None, None, Some(rule)) => rule.apply(&field),
+            (None, None, None) => field.clone(),
+        };
+        Self {
+            constant: format_ident!("{}", field.to_ascii_uppercase(), span = ident.span()),
+            variant: format_ident!("{}", pascal_case(&field), span = ident.span()),
+            name,
+        }
+    }
+
+    /// the const and the variant of a field must not be the ones of a previous field, such as
+    /// `release_year` and `releaseYear` which both give `ReleaseYear`
+    fn check_unique(&self, previous: &[AttributeField]) -> syn::Result<()> {
+        for field in previous {
+            let (generated, ident) = if field.constant == self.constant {
+                ("const", &self.constant)
+            } else if field.variant == self.variant {
+                ("variant", &self.variant)
+            } else {
+                continue;
+            };
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "the {generated} `{ident}` is already generated for the `{}` attribute, \
+                     rename the Rust field or mark it #[algolia(skip)]",
+                    field.name
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// consts and field enum of the attributes of a struct
+fn attribute_names(input: &DeriveInput, fields: &[AttributeField]) -> TokenStream2 {
+    let (vis, name) = (&input.vis, &input.ident);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field_enum = format_ident!("{}Field", name);
+    let constants = fields.iter().map(|field| {
+        let (constant, attribute) = (&field.constant, &field.name);
+        let doc = format!("name of the `{attribute}` attribute in Algolia");
+        quote! {
+            #[doc = #doc]
+            #vis const #constant: &'static str = #attribute;
+        }
+    });
+    let variants = fields
+        .iter()
+        .map(|field| &field.variant)
+        .collect::<Vec<_>>();
+    let attributes = fields.iter().map(|field| &field.name);
+    let enum_doc = format!("attributes of [`{name}`] in Algolia, see `AttributeName`");
+    quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#constants)*
+        }
+
+        #[doc = #enum_doc]
+        #[allow(dead_code)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #vis enum #field_enum {
+            #(#variants,)*
+        }
+
+        #[allow(dead_code)]
+        impl #field_enum {
+            /// every attribute, in the order of the fields
+            #vis const ALL: &'static [Self] = &[#(Self::#variants),*];
+        }
+
+        impl ::EasyAlgolia::algoliaobject::AttributeName for #field_enum {
+            fn attribute_name(&self) -> &str {
+                match *self {
+                    #(Self::#variants => #attributes,)*
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for #field_enum {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(::EasyAlgolia::algoliaobject::AttributeName::attribute_name(self))
+            }
+        }
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut marker = ObjectIdMarker::default();
+    let rename_all = parse_container_attributes(&input.attrs, &mut marker)?;
+    let mut attribute_fields = Vec::new();
+    match &input.data {
+        Data::Struct(data) => {
+            for (position, field) in data.fields.iter().enumerate() {
+                let member = match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(position.into()),
+                };
+                let attributes = parse_field_attributes(field, &member, &mut marker)?;
+                if let (Some(ident), false) = (&field.ident, attributes.skip) {
+                    let attribute = AttributeField::new(ident, attributes, rename_all);
+                    attribute.check_unique(&attribute_fields)?;
+                    attribute_fields.push(attribute);
+                }
+            }
+        }
+        Data::Enum(data) => {
+            let fields = data
+                .variants
+                .iter()
+                .flat_map(|variant| match &variant.fields {
+                    Fields::Named(fields) => fields.named.iter().collect(),
+                    Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+                    Fields::Unit => Vec::new(),
+                });
+            for field in fields {
+                if let Some(attr) = field
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.path().is_ident("algolia"))
+                {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "#[algolia] is not supported on enum fields, use \
+                         #[algolia(object_id = \"expr\")] on the enum",
+                    ));
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "AlgoliaObject can not be derived for unions",
+            ))
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // without object id the default implementation lets Algolia generate one
+    let get_object_id = marker.object_id.map(|object_id| {
+        let expr = object_id.expr();
+        quote! {
+            fn get_object_id(&self) -> ::std::string::String {
+                ::std::string::ToString::to_string(&(#expr))
+            }
+        }
+    });
+    // only named fields are attributes, tuple structs are serialized as arrays
+    let attribute_names =
+        matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Named(_)))
+            .then(|| attribute_names(&input, &attribute_fields));
+    Ok(quote! {
+        impl #impl_generics ::EasyAlgolia::algoliaobject::AlgoliaObject
+            for #name #ty_generics #where_clause
+        {
+            #get_object_id
+        }
 
-    todo!()
+        #attribute_names
+    })
 }
@@ -1,4 +1,19 @@
 //! Algolia Object Id trait
+use crate::{
+    error::{
+        EasyAlgoliaError,
+        ErrorKind,
+    },
+    filter::{
+        Filter,
+        FilterArray,
+    },
+};
+use serde_json::{
+    Map,
+    Value,
+};
+
 /// All object passed to `update_document` or `insert_document` method of `Client` object must impl
 /// AlgoliaObject trait
 pub trait AlgoliaObject: serde::Serialize {
@@ -35,6 +50,106 @@ pub trait AlgoliaObject: serde::Serialize {
     }
 }
 
+/// derive [AlgoliaObject](trait@AlgoliaObject), requires the `derive` feature
+/// the object id is the field marked `#[algolia(object_id)]`, which can be of any type
+/// implementing `Display`, or the expression given with `#[algolia(object_id = "expr")]` on
+/// the type, with `self` in scope
+/// Algolia generates the object id when none is set
+/// structs with named fields also get a `&'static str` const per attribute, named after the
+/// field in upper case, and a `{Struct}Field` enum implementing [AttributeName] with a variant
+/// per attribute
+/// attribute names follow the serde `rename` and `rename_all` attributes, `#[algolia(rename =
+/// "name")]` overrides them when the document is serialized by hand, fields marked
+/// `#[algolia(skip)]` or skipped by serde get neither const nor variant
+/// # Examples
+/// ```
+/// use EasyAlgolia::algoliaobject::AlgoliaObject;
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// struct Game {
+///     #[algolia(object_id)]
+///     id: u32,
+///     name: String,
+/// }
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// #[algolia(object_id = r#"format!("{}-{}", self.platform, self.name)"#)]
+/// struct Release {
+///     name: String,
+///     platform: String,
+/// }
+/// let game = Game {
+///     id: 7,
+///     name: "Halo".into(),
+/// };
+/// assert_eq!(game.get_object_id(), "7");
+/// let release = Release {
+///     name: "halo".into(),
+///     platform: "xbox".into(),
+/// };
+/// assert_eq!(release.get_object_id(), "xbox-halo");
+/// ```
+/// attribute names
+/// ```
+/// use EasyAlgolia::{
+///     algoliaobject::{
+///         AlgoliaObject,
+///         AttributeName,
+///         SearchQuery,
+///     },
+///     filter::Filter,
+/// };
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Game {
+///     name: String,
+///     release_year: u32,
+///     #[serde(skip)]
+///     cached_score: f32,
+/// }
+/// assert_eq!(Game::RELEASE_YEAR, "releaseYear");
+/// assert_eq!(GameField::ALL, &[GameField::Name, GameField::ReleaseYear]);
+/// assert_eq!(GameField::Name.attribute_name(), "name");
+/// let params = SearchQuery::new()
+///     .filter(Filter::gt(GameField::ReleaseYear, 2010))
+///     .attributes_to_retrieve(GameField::ALL)
+///     .to_params()
+///     .unwrap();
+/// assert_eq!(params["filters"], "releaseYear > 2010");
+/// assert_eq!(
+///     params["attributesToRetrieve"],
+///     serde_json::json!(["name", "releaseYear"])
+/// );
+/// ```
+/// fields whose const or variant would be the same are refused, such as `release_year` and
+/// `releaseYear`
+/// ```compile_fail
+/// use EasyAlgolia::algoliaobject::AlgoliaObject;
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// #[allow(non_snake_case)]
+/// struct Game {
+///     release_year: u32,
+///     releaseYear: u32,
+/// }
+/// ```
+/// tuple struct fields have no attribute name to rename
+/// ```compile_fail
+/// use EasyAlgolia::algoliaobject::AlgoliaObject;
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// struct Score(#[algolia(object_id)] u64, #[algolia(rename = "value")] f32);
+/// ```
+/// a document has a single object id
+/// ```compile_fail
+/// use EasyAlgolia::algoliaobject::AlgoliaObject;
+/// #[derive(serde::Serialize, AlgoliaObject)]
+/// struct Game {
+///     #[algolia(object_id)]
+///     id: u32,
+///     #[algolia(object_id)]
+///     name: String,
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use macros::AlgoliaObject;
+
 /// yet to be documented
 pub struct Sort {
     pub(crate) sort: String,
@@ -55,143 +170,573 @@ pub trait Order {
     fn desc(&mut self) -> Sort;
     fn asc(&mut self) -> Sort;
 }
-impl Order for &str {
+
+/// name of a document attribute in Algolia
+/// implemented by the `{Struct}Field` enums generated by `#[derive(AlgoliaObject)]`, so filters,
+/// sorts and settings can name attributes checked by the compiler, strings name attributes as
+/// written
+pub trait AttributeName {
+    fn attribute_name(&self) -> &str;
+}
+
+impl AttributeName for str {
+    fn attribute_name(&self) -> &str {
+        self
+    }
+}
+
+impl<A> AttributeName for &A
+where
+    A: AttributeName + ?Sized,
+{
+    fn attribute_name(&self) -> &str {
+        (**self).attribute_name()
+    }
+}
+
+impl AttributeName for String {
+    fn attribute_name(&self) -> &str {
+        self
+    }
+}
+
+impl<A> Order for A
+where
+    A: AttributeName,
+{
     fn desc(&mut self) -> Sort {
         Sort {
-            sort: format!("{}:desc", self),
+            sort: format!("{}:desc", self.attribute_name()),
         }
     }
     fn asc(&mut self) -> Sort {
         Sort {
-            sort: format!("{}:asc", self),
+            sort: format!("{}:asc", self.attribute_name()),
         }
     }
 }
 
 impl AlgoliaObject for serde_json::value::Value {}
 
+/// operator joining the words of a query to the previous ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryOperator {
+    And,
+    Or,
+}
+
+impl QueryOperator {
+    fn separator(self) -> &'static str {
+        match self {
+            Self::And => " AND ",
+            Self::Or => " OR ",
+        }
+    }
+}
+
+/// typo tolerance of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypoTolerance {
+    /// words with typos match, up to two typos
+    Enabled,
+    /// only exact words match
+    Disabled,
+    /// only the hits with the fewest typos are kept
+    Min,
+    /// the hits with the fewest typos and the ones with one more typo are kept
+    Strict,
+}
+
+impl serde::Serialize for TypoTolerance {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Enabled => serializer.serialize_bool(true),
+            Self::Disabled => serializer.serialize_bool(false),
+            Self::Min => serializer.serialize_str("min"),
+            Self::Strict => serializer.serialize_str("strict"),
+        }
+    }
+}
+
+/// deduplication of the hits sharing the value of the `attributeForDistinct` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(untagged)]
+pub enum Distinct {
+    /// keep only the best hit of each group, or every hit
+    Enabled(bool),
+    /// keep the `n` best hits of each group
+    Count(u32),
+}
+
+impl From<bool> for Distinct {
+    fn from(distinct: bool) -> Self {
+        Self::Enabled(distinct)
+    }
+}
+
+impl From<u32> for Distinct {
+    fn from(count: u32) -> Self {
+        Self::Count(count)
+    }
+}
+
+/// search params other than the query and the filters, named as Algolia names them
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hits_per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes_to_retrieve: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes_to_highlight: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes_to_snippet: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlight_pre_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlight_post_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typo_tolerance: Option<TypoTolerance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distinct: Option<Distinct>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    get_ranking_info: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    click_analytics: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analytics_tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_contexts: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facets: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    around_lat_lng: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    around_radius: Option<u32>,
+}
+
+/// url encoded params string of `params`, arrays and objects are written as json
+pub(crate) fn encode_params(params: Map<String, Value>) -> String {
+    let mut encoded = form_urlencoded::Serializer::new(String::new());
+    for (name, value) in params {
+        match value {
+            Value::String(value) => encoded.append_pair(&name, &value),
+            value => encoded.append_pair(&name, &value.to_string()),
+        };
+    }
+    encoded.finish()
+}
+
+fn attribute_names(attributes: impl IntoIterator<Item = impl AttributeName>) -> Vec<String> {
+    attributes
+        .into_iter()
+        .map(|attribute| attribute.attribute_name().into())
+        .collect()
+}
+
 /// This module provides a struct and methods for constructing search queries with
 /// basic boolean operators, filters, and sorting options.
 ///
 /// It's designed to encapsulate search query logic and construction for potential
 /// integration with external search services or internal search implementations.
 ///
-
 /// # Examples
 ///
 /// ```rust
-/// use algoliaobject::*;  
+/// use EasyAlgolia::{
+///     algoliaobject::*,
+///     filter::Filter,
+/// };
 ///
 /// let query = SearchQuery::new()
 ///     .query("book")
-///     .or_query("novel")
-///     .filter("genre:fiction")
-///     .sort("published_date".desc());
+///     .filter(Filter::eq("genre", "fiction"))
+///     .and_filter(Filter::range("published_year", 1990, 2000));
 ///
 /// // Use the generated query string for your search implementation
-/// println!("Constructed query: {}", query.to_string());
+/// println!("Constructed query: {}", query.to_params_string().unwrap());
+/// ```
 pub struct SearchQuery {
     is_query_set: bool,
-    query: String,
-    filter: Option<String>,
+    /// words of the query, each with the operator joining it to the previous ones
+    query: Vec<(QueryOperator, String)>,
+    filter: Option<Filter>,
+    facet_filters: Option<Filter>,
+    numeric_filters: Option<Filter>,
+    tag_filters: Option<Filter>,
+    optional_filters: Option<Filter>,
+    params: QueryParams,
     pub(crate) sort: Option<String>,
 }
 
-// impl std::fmt::Display for SearchQuery {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         write!(f, "{}", self.build_query())
-//     }
-// }
+impl Default for SearchQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl SearchQuery {
-
-/// yet to be documented
+    /// yet to be documented
     pub fn new() -> Self {
         Self {
-            query: "".into(),
+            query: Vec::new(),
             filter: None,
+            facet_filters: None,
+            numeric_filters: None,
+            tag_filters: None,
+            optional_filters: None,
+            params: QueryParams::default(),
             is_query_set: false,
             sort: None,
         }
     }
 
-    /// yet to be documented
+    /// text searched, replaces the words added before
     pub fn query(mut self, query: &str) -> Self {
         self.is_query_set = true;
-        self.query = query.into();
+        self.query = vec![(QueryOperator::And, query.into())];
         self
     }
-    /// yet to be documented
+    /// append `query` to the text after an ` OR `
+    /// Algolia has no boolean query syntax and searches `OR` as a word like any other
+    #[deprecated(
+        note = "the query text has no OR, match one of several values with `or_filter` or \
+                `Filter::any`"
+    )]
     pub fn or_query(mut self, query: &str) -> Self {
         self.is_query_set = true;
-        if self.query.len() != 0 {
-            self.query.push_str(&format!("|{query}"));
-        } else {
-            self.query.push_str(query);
-        }
-
+        self.query.push((QueryOperator::Or, query.into()));
         self
     }
-    /// yet to be documented
+    /// append `query` to the text after an ` AND `
+    /// Algolia searches `AND` as a word like any other, every word of the query already has to
+    /// match
+    #[deprecated(
+        note = "the query text has no AND, every word of `query` has to match, narrow the \
+                search with `and_filter`"
+    )]
     pub fn and_query(mut self, query: &str) -> Self {
         self.is_query_set = true;
-        if self.query.len() != 0 {
-            self.query.push_str(&format!("&{query}"));
-        } else {
-            self.query.push_str(query);
-        }
+        self.query.push((QueryOperator::And, query.into()));
+        self
+    }
+
+    /// query text, the words joined by ` OR ` and ` AND ` as written
+    pub fn set_query(&self) -> String {
+        self.query
+            .iter()
+            .fold(String::new(), |mut text, (operator, words)| {
+                if !text.is_empty() {
+                    text.push_str(operator.separator());
+                }
+                text.push_str(words);
+                text
+            })
+    }
 
+    /// replace the filter, either a [Filter] or a string in the Algolia filter syntax
+    pub fn filter(mut self, filter: impl Into<Filter>) -> Self {
+        self.filter = Some(filter.into());
         self
     }
 
-    /// yet to be documented
-    pub fn set_query(&mut self) -> &str {
-        self.query = self.query.replace("|", " OR ");
-        self.query = self.query.replace("&", " AND ");
-        &self.query
+    /// documents matching the current filter or `filter`, the current filter is grouped
+    pub fn or_filter(mut self, filter: impl Into<Filter>) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(current) => current.or(filter),
+            None => filter.into(),
+        });
+        self
     }
 
-    pub fn filter(mut self, filter: &str) -> Self {
-        if let Some(ref mut s_filter) = self.filter {
-            *s_filter = filter.into();
-        } else {
-            self.filter = Some(String::from(filter));
-        }
+    /// documents matching the current filter and `filter`, the current filter is grouped
+    pub fn and_filter(mut self, filter: impl Into<Filter>) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(current) => current.and(filter),
+            None => filter.into(),
+        });
         self
     }
 
-    /// yet to be documented
-    pub fn or_filter(mut self, filter: &str) -> Self {
-        if let Some(ref mut s_filter) = self.filter {
-            s_filter.push_str(&format!("|{filter}"));
-        } else {
-            self.filter = Some(String::from(filter));
-        }
+    /// filter rendered to the Algolia filter syntax, empty when no filter is set
+    pub fn set_filter(&self) -> String {
+        self.filter
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    /// replace the `facetFilters` of the search, an AND of facet filters or OR groups of facet
+    /// filters, see [FilterArray]
+    /// # Usage
+    /// ```
+    /// use EasyAlgolia::{
+    ///     algoliaobject::SearchQuery,
+    ///     filter::Filter,
+    /// };
+    /// let query = SearchQuery::new()
+    ///     .facet_filters(
+    ///         Filter::eq("genre", "rpg")
+    ///             .or(Filter::eq("genre", "action"))
+    ///             .and(Filter::ne("platform", "pc")),
+    ///     )
+    ///     .numeric_filters(Filter::range("year", 2010, 2020))
+    ///     .tag_filters(!Filter::tag("beta"))
+    ///     .optional_filters(Filter::eq("studio", "Bungie").score(2));
+    /// assert!(query.validate().is_ok());
+    /// ```
+    pub fn facet_filters(mut self, filters: impl Into<Filter>) -> Self {
+        self.facet_filters = Some(filters.into());
         self
     }
 
-    /// yet to be documented
-    pub fn and_filter(mut self, filter: &str) -> Self {
-        if let Some(ref mut s_filter) = self.filter {
-            s_filter.push_str(&format!("&{filter}"));
-        } else {
-            self.filter = Some(String::from(filter));
+    /// replace the `numericFilters` of the search, an AND of comparisons and ranges or OR
+    /// groups of them
+    pub fn numeric_filters(mut self, filters: impl Into<Filter>) -> Self {
+        self.numeric_filters = Some(filters.into());
+        self
+    }
+
+    /// replace the `tagFilters` of the search, an AND of tags or OR groups of tags
+    pub fn tag_filters(mut self, filters: impl Into<Filter>) -> Self {
+        self.tag_filters = Some(filters.into());
+        self
+    }
+
+    /// replace the `optionalFilters` of the search, facet filters ranking the documents matching
+    /// them first without filtering the others out, a [score](Filter::score) weights them
+    pub fn optional_filters(mut self, filters: impl Into<Filter>) -> Self {
+        self.optional_filters = Some(filters.into());
+        self
+    }
+
+    /// filter array params that are set
+    fn filter_arrays(&self) -> impl Iterator<Item = (FilterArray, &Filter)> {
+        [
+            (FilterArray::Facet, &self.facet_filters),
+            (FilterArray::Numeric, &self.numeric_filters),
+            (FilterArray::Tag, &self.tag_filters),
+            (FilterArray::Optional, &self.optional_filters),
+        ]
+        .into_iter()
+        .filter_map(|(array, filters)| filters.as_ref().map(|filters| (array, filters)))
+    }
+
+    /// check Algolia accepts the filters of the search, see [Filter::validate] and
+    /// [Filter::to_array]
+    /// searches are validated before being sent
+    pub fn validate(&self) -> Result<(), EasyAlgoliaError> {
+        if let Some(filter) = &self.filter {
+            filter.validate()?;
         }
+        self.filter_arrays()
+            .try_for_each(|(array, filters)| filters.to_array(array).map(drop))
+    }
+
+    /// page of hits to fetch, starts at zero
+    pub fn page(mut self, page: u32) -> Self {
+        self.params.page = Some(page);
         self
     }
 
-    /// yet to be documented
-    pub fn set_filter(&mut self) -> &str {
-        if let Some(ref mut s_filter) = self.filter {
-            *s_filter = s_filter.replace("|", " OR ");
-            *s_filter = s_filter.replace("&", " AND ");
-            s_filter
-        } else {
-            ""
+    /// number of hits per page
+    pub fn hits_per_page(mut self, hits_per_page: u32) -> Self {
+        self.params.hits_per_page = Some(hits_per_page);
+        self
+    }
+
+    /// position of the first hit to fetch, used with [length](SearchQuery::length) instead of
+    /// pages
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.params.offset = Some(offset);
+        self
+    }
+
+    /// number of hits to fetch from the [offset](SearchQuery::offset)
+    pub fn length(mut self, length: u32) -> Self {
+        self.params.length = Some(length);
+        self
+    }
+
+    /// attributes sent back for each hit, `*` retrieves all of them
+    pub fn attributes_to_retrieve(
+        mut self,
+        attributes: impl IntoIterator<Item = impl AttributeName>,
+    ) -> Self {
+        self.params.attributes_to_retrieve = Some(attribute_names(attributes));
+        self
+    }
+
+    /// attributes whose matching words are highlighted
+    pub fn attributes_to_highlight(
+        mut self,
+        attributes: impl IntoIterator<Item = impl AttributeName>,
+    ) -> Self {
+        self.params.attributes_to_highlight = Some(attribute_names(attributes));
+        self
+    }
+
+    /// attributes cut down to a snippet around the matching words, `description:20` limits the
+    /// snippet to 20 words
+    pub fn attributes_to_snippet(
+        mut self,
+        attributes: impl IntoIterator<Item = impl AttributeName>,
+    ) -> Self {
+        self.params.attributes_to_snippet = Some(attribute_names(attributes));
+        self
+    }
+
+    /// tag written before the highlighted words, `<em>` by default
+    pub fn highlight_pre_tag(mut self, tag: &str) -> Self {
+        self.params.highlight_pre_tag = Some(tag.into());
+        self
+    }
+
+    /// tag written after the highlighted words, `</em>` by default
+    pub fn highlight_post_tag(mut self, tag: &str) -> Self {
+        self.params.highlight_post_tag = Some(tag.into());
+        self
+    }
+
+    /// how words with typos match
+    pub fn typo_tolerance(mut self, typo_tolerance: TypoTolerance) -> Self {
+        self.params.typo_tolerance = Some(typo_tolerance);
+        self
+    }
+
+    /// deduplicate the hits, either `true`, `false` or the number of hits kept per group
+    pub fn distinct(mut self, distinct: impl Into<Distinct>) -> Self {
+        self.params.distinct = Some(distinct.into());
+        self
+    }
+
+    /// send back the ranking details of each hit in `_rankingInfo`
+    pub fn get_ranking_info(mut self, get_ranking_info: bool) -> Self {
+        self.params.get_ranking_info = Some(get_ranking_info);
+        self
+    }
+
+    /// send back a `queryID` to track the clicks and conversions of the search
+    pub fn click_analytics(mut self, click_analytics: bool) -> Self {
+        self.params.click_analytics = Some(click_analytics);
+        self
+    }
+
+    /// tags grouping the search in the analytics
+    pub fn analytics_tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.params.analytics_tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// contexts enabling the rules conditioned on them
+    pub fn rule_contexts(mut self, contexts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.params.rule_contexts = Some(contexts.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// identifier of the end user, for analytics and personalization
+    pub fn user_token(mut self, user_token: &str) -> Self {
+        self.params.user_token = Some(user_token.into());
+        self
+    }
+
+    /// attributes whose value counts are sent back in `facets`, `*` counts every facet
+    pub fn facets(mut self, attributes: impl IntoIterator<Item = impl AttributeName>) -> Self {
+        self.params.facets = Some(attribute_names(attributes));
+        self
+    }
+
+    /// rank the hits by distance to a location
+    pub fn around_lat_lng(mut self, lat: f64, lng: f64) -> Self {
+        self.params.around_lat_lng = Some(format!("{lat},{lng}"));
+        self
+    }
+
+    /// only keep the hits within `meters` of [around_lat_lng](SearchQuery::around_lat_lng)
+    pub fn around_radius(mut self, meters: u32) -> Self {
+        self.params.around_radius = Some(meters);
+        self
+    }
+
+    /// search params as a json object, the body Algolia expects
+    /// the query, the filter and the filter arrays are sent along the other params
+    /// # Usage
+    /// ```
+    /// use EasyAlgolia::{
+    ///     algoliaobject::SearchQuery,
+    ///     filter::Filter,
+    /// };
+    /// let params = SearchQuery::new()
+    ///     .query("halo")
+    ///     .filter(Filter::eq("platform", "xbox"))
+    ///     .page(2)
+    ///     .hits_per_page(10)
+    ///     .attributes_to_retrieve(["name", "platform"])
+    ///     .to_params()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     serde_json::Value::Object(params),
+    ///     serde_json::json!({
+    ///         "query": "halo",
+    ///         "filters": "platform:\"xbox\"",
+    ///         "page": 2,
+    ///         "hitsPerPage": 10,
+    ///         "attributesToRetrieve": ["name", "platform"],
+    ///     })
+    /// );
+    /// ```
+    /// # Error
+    /// returns [`Err(EasyAlgoliaError)`](crate::error::EasyAlgoliaError) when
+    /// [validate](SearchQuery::validate) refuses the filters or a [sort](SearchQuery::sort) is
+    /// set
+    pub fn to_params(&self) -> Result<Map<String, Value>, EasyAlgoliaError> {
+        self.validate()?;
+        if let Some(sort) = &self.sort {
+            return Err(EasyAlgoliaError::new(
+                ErrorKind::InvalidRequest,
+                Some(format!(
+                    "search has no sort param, search a replica index sorted by {sort} instead"
+                )),
+            ));
+        }
+        let mut params = Map::new();
+        if self.is_query_set {
+            params.insert("query".into(), self.set_query().into());
+        }
+        if let Some(filter) = self.filter.as_ref().filter(|filter| !filter.is_empty()) {
+            params.insert("filters".into(), filter.to_string().into());
         }
+        for (array, filters) in self.filter_arrays() {
+            params.insert(array.param().into(), filters.to_array(array)?);
+        }
+        if let Value::Object(other) = serde_json::to_value(&self.params)? {
+            params.extend(other);
+        }
+        Ok(params)
     }
 
-    /// yet to be documented
+    /// search params as the url encoded `params` string of a search request, arrays and objects
+    /// are written as json
+    pub fn to_params_string(&self) -> Result<String, EasyAlgoliaError> {
+        Ok(encode_params(self.to_params()?))
+    }
+
+    /// attributes to sort the hits by, several calls sort by each attribute in turn
+    /// Algolia has no sort param and orders the hits by the ranking of the index, a search with
+    /// a sort is refused by [to_params](SearchQuery::to_params)
+    #[deprecated(
+        note = "search has no sort param, search a replica index ranked by these attributes \
+                instead"
+    )]
     pub fn sort(mut self, order: impl QuerySort) -> Self {
         if let Some(ref mut sort) = self.sort {
             sort.push_str(&format!(",{}", order.get_query()))
@@ -201,76 +746,253 @@ impl SearchQuery {
         self
     }
 
-    /// yet to be documented
-    pub fn build_query(&mut self) -> String {
-        self.set_query();
-        self.set_filter();
-        let mut query: String = "".into();
-        if self.is_query_set {
-            query.push_str(&format!("query={}", self.query));
-        }
-        // let query = &mut self.query;
-        if let Some(ref s_filter) = self.filter {
-            if self.is_query_set {
-                query.push_str(&format!("&filter={s_filter}"))
-            } else {
-                query.push_str(&format!("filter={s_filter}"))
-            }
-        }
-        if let Some(ref sort) = self.sort {
-            if self.is_query_set {
-                query.push_str(&format!("&sort={sort}"))
-            } else {
-                query.push_str(&format!("sort={sort}"))
-            }
-        }
-        query
+    /// url encoded params string sent to Algolia, same as
+    /// [to_params_string](SearchQuery::to_params_string)
+    /// # Error
+    /// returns [`Err(EasyAlgoliaError)`](crate::error::EasyAlgoliaError) when
+    /// [validate](SearchQuery::validate) refuses the filters
+    #[deprecated(note = "use `to_params_string`")]
+    pub fn build_query(&self) -> Result<String, EasyAlgoliaError> {
+        self.to_params_string()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+
+    /// decode a url encoded params string
+    fn parse(params: &str) -> Vec<(String, String)> {
+        form_urlencoded::parse(params.as_bytes())
+            .into_owned()
+            .collect()
+    }
     #[test]
+    #[allow(deprecated)]
     fn test_query_builder() {
-        let mut query = SearchQuery::new()
+        let query = SearchQuery::new()
             .query("orange")
             .or_query("banana")
             .and_query("mango");
-        assert!(String::from("orange OR banana AND mango").eq(query.set_query()));
+        assert!(String::from("orange OR banana AND mango").eq(&query.set_query()));
     }
     #[test]
     fn test_filter_builder() {
-        let mut query = SearchQuery::new()
+        let query = SearchQuery::new()
             .filter("name:faizal")
             .or_filter("price:>10")
             .and_filter("language:rust");
 
-        assert!(String::from("name:faizal OR price:>10 AND language:rust").eq(query.set_filter()));
+        assert!(
+            String::from("(name:faizal OR price:>10) AND language:rust").eq(&query.set_filter())
+        );
+    }
+
+    #[test]
+    fn test_filter_values_are_not_rewritten() {
+        let query = SearchQuery::new()
+            .filter(Filter::eq("brand", "Tom & Jerry"))
+            .or_filter(Filter::eq("brand", "Black|White"));
+        assert_eq!(
+            query.set_filter(),
+            r#"brand:"Tom & Jerry" OR brand:"Black|White""#
+        );
+        assert_eq!(SearchQuery::new().set_filter(), "");
+    }
+
+    #[test]
+    fn test_filter_arrays_in_query() {
+        let query = SearchQuery::new()
+            .facet_filters(Filter::eq("genre", "rpg").or(Filter::eq("genre", "action")))
+            .numeric_filters(Filter::ge("price", 10))
+            .tag_filters(!Filter::tag("beta"))
+            .optional_filters(Filter::eq("studio", "Bungie").score(2));
+        assert!(query.validate().is_ok());
+        assert_eq!(
+            parse(&query.to_params_string().unwrap()),
+            [
+                ("facetFilters", r#"[["genre:rpg","genre:action"]]"#),
+                ("numericFilters", r#"["price >= 10"]"#),
+                ("optionalFilters", r#"["studio:Bungie<score=2>"]"#),
+                ("tagFilters", r#"["-beta"]"#),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+        );
+        let query = SearchQuery::new().facet_filters(Filter::gt("price", 10));
+        assert!(query.validate().is_err());
+        assert!(query.to_params_string().is_err());
+        let query =
+            SearchQuery::new().filter(Filter::eq("genre", "rpg").or(Filter::gt("price", 10)));
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_search_params() {
+        let snippet: &[&str] = &["description:20"];
+        let query = SearchQuery::new()
+            .page(1)
+            .hits_per_page(5)
+            .offset(10)
+            .length(3)
+            .attributes_to_retrieve(["name"])
+            .attributes_to_highlight(vec![String::from("name")])
+            .attributes_to_snippet(snippet)
+            .highlight_pre_tag("<b>")
+            .highlight_post_tag("</b>")
+            .typo_tolerance(TypoTolerance::Min)
+            .distinct(2)
+            .get_ranking_info(true)
+            .click_analytics(true)
+            .analytics_tags(["mobile"])
+            .rule_contexts(vec![String::from("summer")])
+            .user_token("user-42")
+            .facets(["genre", "platform"])
+            .around_lat_lng(48.85, 2.35)
+            .around_radius(1000);
+        assert_eq!(
+            Value::Object(query.to_params().unwrap()),
+            serde_json::json!({
+                "page": 1,
+                "hitsPerPage": 5,
+                "offset": 10,
+                "length": 3,
+                "attributesToRetrieve": ["name"],
+                "attributesToHighlight": ["name"],
+                "attributesToSnippet": ["description:20"],
+                "highlightPreTag": "<b>",
+                "highlightPostTag": "</b>",
+                "typoTolerance": "min",
+                "distinct": 2,
+                "getRankingInfo": true,
+                "clickAnalytics": true,
+                "analyticsTags": ["mobile"],
+                "ruleContexts": ["summer"],
+                "userToken": "user-42",
+                "facets": ["genre", "platform"],
+                "aroundLatLng": "48.85,2.35",
+                "aroundRadius": 1000,
+            })
+        );
+        let params = SearchQuery::new()
+            .typo_tolerance(TypoTolerance::Disabled)
+            .distinct(true)
+            .to_params()
+            .unwrap();
+        assert_eq!(params["typoTolerance"], false);
+        assert_eq!(params["distinct"], true);
+        assert!(SearchQuery::new().to_params().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_params_string() {
+        let query = SearchQuery::new()
+            .query("c++ rust")
+            .filter(Filter::eq("brand", "Tom & Jerry"))
+            .facet_filters(Filter::eq("genre", "rpg"))
+            .attributes_to_retrieve(["name", "genre"])
+            .hits_per_page(10);
+        assert_eq!(
+            query.to_params_string().unwrap(),
+            "attributesToRetrieve=%5B%22name%22%2C%22genre%22%5D\
+             &facetFilters=%5B%22genre%3Arpg%22%5D&filters=brand%3A%22Tom+%26+Jerry%22\
+             &hitsPerPage=10&query=c%2B%2B+rust"
+        );
+        let query = SearchQuery::new().facet_filters(Filter::gt("price", 10));
+        assert!(query.to_params_string().is_err());
+        // an empty OR group would otherwise be sent as an empty filter matching everything
+        let query = SearchQuery::new().filter(Filter::any(Vec::<Filter>::new()));
+        assert!(query.to_params_string().is_err());
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_sort_builder() {
-        let mut query = SearchQuery::new()
+        let query = SearchQuery::new()
             .sort("price".desc())
             .sort("launch_date".asc())
             .sort("name".desc());
-        assert!(String::from("sort=price:desc,launch_date:asc,name:desc").eq(&query.build_query()))
+        assert_eq!(
+            query.sort.as_deref(),
+            Some("price:desc,launch_date:asc,name:desc")
+        );
+        // search has no sort param, the sort is refused rather than dropped
+        let err = query.to_params().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidRequest);
+        assert!(query.to_params_string().is_err());
     }
 
     #[test]
+    #[allow(deprecated)]
     fn build_query_test() {
-        let mut query = SearchQuery::new()
+        let query = SearchQuery::new()
             .query("phone")
             .or_query("laptop")
             .filter("brand:apple")
-            .and_filter("color:red")
-            .sort("cost".desc())
-            .sort("hype".asc());
-        //dbg!(query.build_query());
-        assert!(
-            String::from("query=phone OR laptop&filter=brand:apple AND color:red&sort=cost:desc,hype:asc")
-                .eq(&query.build_query())
-        )
+            .and_filter("color:red");
+        assert_eq!(
+            query.build_query().unwrap(),
+            "filters=brand%3Aapple+AND+color%3Ared&query=phone+OR+laptop"
+        );
+        assert_eq!(
+            query.build_query().unwrap(),
+            query.to_params_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_query_round_trip() {
+        let query = SearchQuery::new()
+            .query("c++ & rust a|b 100%=done")
+            .filter(Filter::eq("brand", "Tom & Jerry"))
+            .or_filter("tag:a|b");
+        assert_eq!(
+            parse(&query.to_params_string().unwrap()),
+            [
+                ("filters", r#"brand:"Tom & Jerry" OR tag:a|b"#),
+                ("query", "c++ & rust a|b 100%=done"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+        );
+        assert_eq!(query.set_query(), "c++ & rust a|b 100%=done");
+        // building twice gives the same string
+        assert_eq!(
+            query.to_params_string().unwrap(),
+            query.to_params_string().unwrap()
+        );
+
+        assert_eq!(
+            parse(&SearchQuery::new().query("").to_params_string().unwrap()),
+            [("query".into(), "".into())]
+        );
+        assert_eq!(SearchQuery::new().to_params_string().unwrap(), "");
+        // query replaces the words set before
+        let query = SearchQuery::new().query("old").query("new");
+        assert_eq!(query.set_query(), "new");
+    }
+
+    #[test]
+    fn test_params_string_round_trip() {
+        let query = SearchQuery::new()
+            .query("c++ & rust tom=jerry")
+            .filter(Filter::eq("brand", "A&B").and(Filter::tag("50%")))
+            .facet_filters(Filter::eq("platform", "pc").or(Filter::eq("platform", "+1")))
+            .attributes_to_retrieve(["name", "a&b"])
+            .highlight_pre_tag("<em class=\"hit\">")
+            .page(3);
+        let params = query.to_params().unwrap();
+        let parsed: Map<String, Value> = parse(&query.to_params_string().unwrap())
+            .into_iter()
+            .map(|(key, value)| {
+                // strings are sent as is, everything else as json
+                let value = match &params[&key] {
+                    Value::String(_) => Value::String(value),
+                    _ => serde_json::from_str(&value).unwrap(),
+                };
+                (key, value)
+            })
+            .collect();
+        assert_eq!(parsed, params);
+        assert_eq!(params["query"], "c++ & rust tom=jerry");
+        assert_eq!(params["filters"], r#"brand:"A&B" AND _tags:"50%""#);
     }
 }
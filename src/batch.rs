@@ -0,0 +1,391 @@
+//! Batch write operations
+//! batching sends many writes in a single request instead of one http call per document,
+//! see [client.batch_async](crate::Client::batch_async) and
+//! [client.multi_batch_async](crate::Client::multi_batch_async)
+use crate::{
+    algoliaobject::AlgoliaObject,
+    error::EasyAlgoliaError,
+    request::AlgoliaRequest,
+    response::{
+        BatchResponse,
+        MultiBatchResponse,
+        TaskId,
+    },
+    Client,
+    Index,
+    ObjectId,
+};
+use http::Method;
+use serde::{
+    ser::{
+        Error,
+        SerializeStruct,
+    },
+    Serialize,
+    Serializer,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// default number of operations sent per batch request
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// a single write operation of a batch
+/// # Usage
+/// ```ignore
+///    let games = vec![
+///        BatchOperation::upsert(last_of_us),
+///        BatchOperation::upsert(god_of_war),
+///        BatchOperation::DeleteObject("halo".into()),
+///    ];
+///    let response = client.batch_async(&index, games).await?;
+/// ```
+pub enum BatchOperation<T> {
+    /// insert the document, Algolia generates the object id
+    AddObject(T),
+    /// replace the document having the same object id, or insert it
+    UpdateObject(T),
+    /// update only the attributes present in the document, create it if missing
+    PartialUpdateObject(T),
+    /// update only the attributes present in the document, ignore it if missing
+    PartialUpdateObjectNoCreate(T),
+    /// delete the document with the given object id
+    DeleteObject(ObjectId),
+}
+
+impl<T> BatchOperation<T>
+where
+    T: AlgoliaObject,
+{
+    /// same semantic as [put_document_async](crate::Client::put_document_async)
+    /// documents with an empty [get_object_id](crate::algoliaobject::AlgoliaObject::get_object_id)
+    /// are added with an Algolia generated id, others replace the document with the same id
+    pub fn upsert(document: T) -> Self {
+        match document.get_object_id().as_str() {
+            "" => Self::AddObject(document),
+            _ => Self::UpdateObject(document),
+        }
+    }
+
+    fn action(&self) -> &'static str {
+        match self {
+            Self::AddObject(_) => "addObject",
+            Self::UpdateObject(_) => "updateObject",
+            Self::PartialUpdateObject(_) => "partialUpdateObject",
+            Self::PartialUpdateObjectNoCreate(_) => "partialUpdateObjectNoCreate",
+            Self::DeleteObject(_) => "deleteObject",
+        }
+    }
+
+    /// body of the operation, documents get their object id injected as `objectID`
+    fn body(&self) -> Result<Value, serde_json::Error> {
+        let document = match self {
+            Self::DeleteObject(object_id) => {
+                return Ok(serde_json::json!({ "objectID": object_id.get_object_id() }))
+            }
+            Self::AddObject(document)
+            | Self::UpdateObject(document)
+            | Self::PartialUpdateObject(document)
+            | Self::PartialUpdateObjectNoCreate(document) => document,
+        };
+        let mut body = serde_json::to_value(document)?;
+        let object_id = document.get_object_id();
+        if let (Value::Object(body), false) = (&mut body, object_id.is_empty()) {
+            body.insert("objectID".into(), Value::String(object_id));
+        }
+        Ok(body)
+    }
+}
+
+impl<T> Serialize for BatchOperation<T>
+where
+    T: AlgoliaObject,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut operation = serializer.serialize_struct("BatchOperation", 2)?;
+        operation.serialize_field("action", self.action())?;
+        operation.serialize_field("body", &self.body().map_err(S::Error::custom)?)?;
+        operation.end()
+    }
+}
+
+/// a write operation targeting its own index, used by
+/// [client.multi_batch_async](crate::Client::multi_batch_async)
+/// # Usage
+/// ```ignore
+///    let products: Index = "Products".into();
+///    let brands: Index = "Brands".into();
+///    let operations = vec![
+///        MultiBatchOperation::new(products, BatchOperation::upsert(phone)),
+///        MultiBatchOperation::new(brands, BatchOperation::upsert(apple)),
+///    ];
+///    let response = client.multi_batch_async(operations).await?;
+/// ```
+pub struct MultiBatchOperation<T> {
+    index: Index,
+    operation: BatchOperation<T>,
+}
+
+impl<T> MultiBatchOperation<T> {
+    /// apply `operation` on `index`
+    pub fn new(index: Index, operation: BatchOperation<T>) -> Self {
+        Self { index, operation }
+    }
+}
+
+impl<T> Serialize for MultiBatchOperation<T>
+where
+    T: AlgoliaObject,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut operation = serializer.serialize_struct("MultiBatchOperation", 3)?;
+        operation.serialize_field("action", self.operation.action())?;
+        operation.serialize_field("indexName", self.index.index())?;
+        operation.serialize_field("body", &self.operation.body().map_err(S::Error::custom)?)?;
+        operation.end()
+    }
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a, O> {
+    requests: &'a [O],
+}
+
+#[derive(serde::Deserialize)]
+struct MultiBatchChunkResponse {
+    #[serde(rename = "taskID")]
+    task_ids: HashMap<String, TaskId>,
+    #[serde(rename = "objectIDs", default)]
+    object_ids: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct BatchChunkResponse {
+    #[serde(rename = "taskID")]
+    task_id: TaskId,
+    #[serde(rename = "objectIDs", default)]
+    object_ids: Vec<String>,
+}
+
+impl BatchChunkResponse {
+    fn add_to(self, response: &mut BatchResponse) {
+        response.task_ids.push(self.task_id);
+        response.object_ids.extend(self.object_ids);
+    }
+}
+
+impl MultiBatchChunkResponse {
+    fn add_to(self, response: &mut MultiBatchResponse) {
+        response.task_ids.extend(self.task_ids);
+        response.object_ids.extend(self.object_ids);
+    }
+}
+
+impl Client {
+    /// one request per chunk of `batch_size` operations
+    fn batch_requests<O>(
+        &self,
+        path: &str,
+        operations: &[O],
+    ) -> Result<Vec<AlgoliaRequest>, EasyAlgoliaError>
+    where
+        O: Serialize,
+    {
+        operations
+            .chunks(self.batch_size)
+            .map(|chunk| {
+                AlgoliaRequest::write(Method::POST, String::from(path))
+                    .json(&BatchRequest { requests: chunk })
+            })
+            .collect()
+    }
+
+    /// send many write operations to the given index
+    /// operations are split in chunks of the client batch size (1000 by default, see
+    /// [set_batch_size](crate::client_builder::ClientBuilder::set_batch_size)), one request is
+    /// made per chunk and chunks are sent one after the other
+    /// # Examples
+    /// ```ignore
+    ///    let operations = games.into_iter().map(BatchOperation::upsert).collect();
+    ///    let response = client.batch_async(&index, operations).await?;
+    ///    println!("{} documents written", response.object_ids.len());
+    /// ```
+    pub async fn batch_async<T>(
+        &self,
+        index: &Index,
+        operations: Vec<BatchOperation<T>>,
+    ) -> Result<BatchResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let path = format!("/1/indexes/{}/batch", index.index());
+        let mut response = BatchResponse::default();
+        for request in self.batch_requests(&path, &operations)? {
+            let chunk_response: BatchChunkResponse = self.send_request(request).await?;
+            chunk_response.add_to(&mut response);
+        }
+        Ok(response)
+    }
+
+    /// same as [batch_async](crate::Client::batch_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn batch<T>(
+        &self,
+        index: &Index,
+        operations: Vec<BatchOperation<T>>,
+    ) -> Result<BatchResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let path = format!("/1/indexes/{}/batch", index.index());
+        let mut response = BatchResponse::default();
+        for request in self.batch_requests(&path, &operations)? {
+            let chunk_response: BatchChunkResponse = self.send_request_blocking(request)?;
+            chunk_response.add_to(&mut response);
+        }
+        Ok(response)
+    }
+
+    /// send write operations touching several indexes through `/1/indexes/*/batch`
+    /// operations are chunked like [batch_async](crate::Client::batch_async), the response
+    /// holds the last task id of every index, Algolia runs the tasks of an index in order so
+    /// waiting for it covers all the operations on that index
+    /// # Examples
+    /// ```ignore
+    ///    let operations = vec![
+    ///        MultiBatchOperation::new("Products".into(), BatchOperation::upsert(phone)),
+    ///        MultiBatchOperation::new("Brands".into(), BatchOperation::upsert(apple)),
+    ///    ];
+    ///    let response = client.multi_batch_async(operations).await?;
+    ///    let product_task = response.task_ids["Products"];
+    /// ```
+    pub async fn multi_batch_async<T>(
+        &self,
+        operations: Vec<MultiBatchOperation<T>>,
+    ) -> Result<MultiBatchResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let mut response = MultiBatchResponse::default();
+        for request in self.batch_requests("/1/indexes/*/batch", &operations)? {
+            let chunk_response: MultiBatchChunkResponse = self.send_request(request).await?;
+            chunk_response.add_to(&mut response);
+        }
+        Ok(response)
+    }
+
+    /// same as [multi_batch_async](crate::Client::multi_batch_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn multi_batch<T>(
+        &self,
+        operations: Vec<MultiBatchOperation<T>>,
+    ) -> Result<MultiBatchResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let mut response = MultiBatchResponse::default();
+        for request in self.batch_requests("/1/indexes/*/batch", &operations)? {
+            let chunk_response: MultiBatchChunkResponse = self.send_request_blocking(request)?;
+            chunk_response.add_to(&mut response);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Game {
+        name: String,
+    }
+
+    impl AlgoliaObject for Game {
+        fn get_object_id(&self) -> String {
+            self.name.to_lowercase()
+        }
+    }
+
+    #[test]
+    fn test_batch_operation_serialize() {
+        let operations = vec![
+            BatchOperation::upsert(Game {
+                name: "Halo".into(),
+            }),
+            BatchOperation::PartialUpdateObjectNoCreate(Game {
+                name: "Doom".into(),
+            }),
+            BatchOperation::DeleteObject("quake".into()),
+        ];
+        let json = serde_json::to_value(BatchRequest {
+            requests: &operations,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "requests": [
+                { "action": "updateObject", "body": { "name": "Halo", "objectID": "halo" } },
+                {
+                    "action": "partialUpdateObjectNoCreate",
+                    "body": { "name": "Doom", "objectID": "doom" }
+                },
+                { "action": "deleteObject", "body": { "objectID": "quake" } },
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_upsert_without_object_id_adds() {
+        let operation = BatchOperation::upsert(serde_json::json!({ "name": "Halo" }));
+        assert!(matches!(operation, BatchOperation::AddObject(_)));
+        let json = serde_json::to_value(&operation).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "action": "addObject", "body": { "name": "Halo" } })
+        );
+    }
+
+    #[test]
+    fn test_multi_batch_operation_serialize() {
+        let operations = vec![
+            MultiBatchOperation::new(
+                "Games".into(),
+                BatchOperation::upsert(Game {
+                    name: "Halo".into(),
+                }),
+            ),
+            MultiBatchOperation::new(
+                "Studios".into(),
+                BatchOperation::DeleteObject("bungie".into()),
+            ),
+        ];
+        let json = serde_json::to_value(BatchRequest {
+            requests: &operations,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "requests": [
+                {
+                    "action": "updateObject",
+                    "indexName": "Games",
+                    "body": { "name": "Halo", "objectID": "halo" }
+                },
+                {
+                    "action": "deleteObject",
+                    "indexName": "Studios",
+                    "body": { "objectID": "bungie" }
+                },
+            ]})
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Browse every document of an index
+//! unlike search, browse is not limited to the first 1000 hits and is meant for exports and
+//! migrations, see [client.browse_async](crate::Client::browse_async)
+use crate::{
+    algoliaobject::SearchQuery,
+    error::EasyAlgoliaError,
+    filter::Filter,
+    request::AlgoliaRequest,
+    Client,
+    Index,
+};
+use futures::{
+    stream,
+    Stream,
+    TryStreamExt,
+};
+use http::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// parameters of a browse, all documents are browsed by default
+/// # Usage
+/// ```
+/// use EasyAlgolia::browse::BrowseParams;
+/// let params = BrowseParams::new()
+///     .filters("platform:playstation")
+///     .attributes_to_retrieve(&["name", "genre"])
+///     .hits_per_page(1000);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BrowseParams {
+    query: Option<String>,
+    filters: Option<Filter>,
+    attributes_to_retrieve: Option<Vec<String>>,
+    hits_per_page: Option<u32>,
+}
+
+impl BrowseParams {
+    /// browse all documents
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// only browse documents matching the query
+    pub fn query(mut self, query: &str) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// only browse documents matching the filters, a [Filter] or a string in the search filter
+    /// syntax
+    pub fn filters(mut self, filters: impl Into<Filter>) -> Self {
+        self.filters = Some(filters.into());
+        self
+    }
+
+    /// attributes sent back for each document
+    pub fn attributes_to_retrieve(mut self, attributes: &[&str]) -> Self {
+        self.attributes_to_retrieve = Some(attributes.iter().map(|a| String::from(*a)).collect());
+        self
+    }
+
+    /// number of documents fetched per request, Algolia caps it to 1000
+    pub fn hits_per_page(mut self, hits_per_page: u32) -> Self {
+        self.hits_per_page = Some(hits_per_page);
+        self
+    }
+
+    /// url encoded params string sent to Algolia, written like the params of a search, fails
+    /// when the filters are invalid
+    fn build_params(&self) -> Result<String, EasyAlgoliaError> {
+        let mut search = SearchQuery::new();
+        if let Some(query) = &self.query {
+            search = search.query(query);
+        }
+        if let Some(filters) = &self.filters {
+            search = search.filter(filters.clone());
+        }
+        if let Some(attributes) = &self.attributes_to_retrieve {
+            search = search.attributes_to_retrieve(attributes);
+        }
+        if let Some(hits_per_page) = self.hits_per_page {
+            search = search.hits_per_page(hits_per_page);
+        }
+        search.to_params_string()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct BrowsePage<T> {
+    hits: Vec<T>,
+    /// absent on the last page
+    cursor: Option<String>,
+}
+
+impl<T> BrowsePage<T> {
+    /// body requesting the next page, `None` on the last page
+    fn next_page(&mut self) -> Option<Value> {
+        // the cursor holds the params of the browse, it is the only thing to send back
+        self.cursor
+            .take()
+            .map(|cursor| serde_json::json!({ "cursor": cursor }))
+    }
+}
+
+fn browse_request(path: &str, body: &Value) -> Result<AlgoliaRequest, EasyAlgoliaError> {
+    AlgoliaRequest::read(Method::POST, String::from(path)).json(body)
+}
+
+impl Client {
+    /// stream every document of the index matching `params`
+    /// pages are fetched lazily while the stream is polled, following the cursor sent back by
+    /// Algolia until the last page, invalid filters are sent back as the first item
+    /// # Examples
+    /// ```ignore
+    ///    use futures::TryStreamExt;
+    ///    let index: Index = "Games".into();
+    ///    let games: Vec<Game> = client
+    ///        .browse_async(&index, BrowseParams::new())
+    ///        .try_collect()
+    ///        .await?;
+    /// ```
+    pub fn browse_async<'a, T>(
+        &'a self,
+        index: &Index,
+        params: BrowseParams,
+    ) -> impl Stream<Item = Result<T, EasyAlgoliaError>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+    {
+        let path = format!("/1/indexes/{}/browse", index.index());
+        let first_page = params
+            .build_params()
+            .map(|params| serde_json::json!({ "params": params }));
+        stream::try_unfold(Some(first_page), move |body: Option<Result<Value, _>>| {
+            let path = path.clone();
+            async move {
+                let Some(body) = body else {
+                    return Ok::<_, EasyAlgoliaError>(None);
+                };
+                let mut page: BrowsePage<T> =
+                    self.send_request(browse_request(&path, &body?)?).await?;
+                let next_page = page.next_page().map(Ok);
+                Ok(Some((page.hits, next_page)))
+            }
+        })
+        .map_ok(|hits| stream::iter(hits.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// same as [browse_async](crate::Client::browse_async) but returns a blocking iterator
+    /// each page is fetched by a blocking http client when the iterator reaches it
+    #[cfg(feature = "blocking")]
+    pub fn browse<'a, T>(
+        &'a self,
+        index: &Index,
+        params: BrowseParams,
+    ) -> impl Iterator<Item = Result<T, EasyAlgoliaError>> + 'a
+    where
+        T: DeserializeOwned + 'a,
+    {
+        let path = format!("/1/indexes/{}/browse", index.index());
+        let mut next_page = Some(
+            params
+                .build_params()
+                .map(|params| serde_json::json!({ "params": params })),
+        );
+        let mut hits = Vec::new().into_iter();
+        std::iter::from_fn(move || loop {
+            if let Some(hit) = hits.next() {
+                return Some(Ok(hit));
+            }
+            let body = next_page.take()?;
+            let page = body
+                .and_then(|body| browse_request(&path, &body))
+                .and_then(|request| self.send_request_blocking::<BrowsePage<T>>(request));
+            match page {
+                Ok(mut page) => {
+                    next_page = page.next_page().map(Ok);
+                    hits = page.hits.into_iter();
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_browse_params_are_encoded() {
+        let params = BrowseParams::new()
+            .query("last of us")
+            .filters("platform:playstation AND year > 2010")
+            .attributes_to_retrieve(&["name", "genre"])
+            .hits_per_page(500);
+        assert_eq!(
+            params.build_params().unwrap(),
+            "attributesToRetrieve=%5B%22name%22%2C%22genre%22%5D\
+             &filters=platform%3Aplaystation+AND+year+%3E+2010&hitsPerPage=500&query=last+of+us"
+        );
+        assert_eq!(BrowseParams::new().build_params().unwrap(), "");
+        assert_eq!(BrowseParams::new().filters("").build_params().unwrap(), "");
+    }
+
+    #[test]
+    fn test_browse_params_match_search_params() {
+        let filter = Filter::eq("platform", "playstation").and(Filter::gt("year", 2010));
+        let browse = BrowseParams::new()
+            .query("last of us")
+            .filters(filter.clone())
+            .attributes_to_retrieve(&["name", "genre"])
+            .hits_per_page(500);
+        let search = SearchQuery::new()
+            .query("last of us")
+            .filter(filter)
+            .attributes_to_retrieve(["name", "genre"])
+            .hits_per_page(500);
+        assert_eq!(
+            browse.build_params().unwrap(),
+            search.to_params_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_browse_params_refuse_empty_or_group() {
+        let params = BrowseParams::new().filters(Filter::any(Vec::<Filter>::new()));
+        assert!(params.build_params().is_err());
+    }
+
+    #[test]
+    fn test_browse_page_without_cursor() {
+        let page: BrowsePage<Value> =
+            serde_json::from_str(r#"{ "hits": [{ "name": "Halo" }], "nbHits": 1 }"#).unwrap();
+        assert!(page.cursor.is_none());
+        assert_eq!(page.hits.len(), 1);
+    }
+}
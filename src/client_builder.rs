@@ -3,21 +3,90 @@
 //! can be used to build client from environment variables
 
 use crate::{
+    batch::DEFAULT_BATCH_SIZE,
     error::{
         EasyAlgoliaError,
         ErrorKind,
     },
+    retry::{
+        DefaultRetryStrategy,
+        Host,
+        RetryStrategy,
+    },
+    task::TaskWaitPolicy,
+    transport::HttpRequester,
     Client,
 };
-use std::mem;
+use http::header::{
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    USER_AGENT,
+};
+use std::{
+    mem,
+    sync::Arc,
+    time::Duration,
+};
 
 use secrecy::{
     ExposeSecret,
     Secret,
 };
+/// timeouts of the requests sent to Algolia
+/// reads and writes have their own timeout like in the official clients, writes carry larger
+/// bodies and take longer to be acknowledged
+/// ```
+/// use std::time::Duration;
+/// use EasyAlgolia::client_builder::Timeouts;
+/// let timeouts = Timeouts {
+///     read: Duration::from_secs(2),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    /// time to establish the connection to a host
+    pub connect: Duration,
+    /// time for a read to complete, from sending the request to reading the whole response
+    pub read: Duration,
+    /// time for a write to complete, from sending the request to reading the whole response
+    pub write: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(2),
+            read: Duration::from_secs(5),
+            write: Duration::from_secs(30),
+        }
+    }
+}
+
+/// agent sent in the `User-Agent` and `X-Algolia-Agent` headers, followed by the segments added
+/// with [add_agent_segment](crate::client_builder::ClientBuilder::add_agent_segment)
+pub const AGENT: &str = concat!("EasyAlgolia/", env!("CARGO_PKG_VERSION"));
+
 pub struct ClientBuilder {
     application_id: Option<Secret<String>>,
     api_key: Option<Secret<String>>,
+    batch_size: usize,
+    task_wait_policy: TaskWaitPolicy,
+    retry_strategy: Option<Arc<dyn RetryStrategy>>,
+    hosts: Option<Vec<Host>>,
+    timeouts: Timeouts,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: usize,
+    headers: Vec<(String, String)>,
+    agent_segments: Vec<String>,
+    requester: Option<Arc<dyn HttpRequester>>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ClientBuilder {
@@ -33,6 +102,16 @@ impl ClientBuilder {
         Self {
             application_id: None,
             api_key: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+            task_wait_policy: TaskWaitPolicy::default(),
+            retry_strategy: None,
+            hosts: None,
+            timeouts: Timeouts::default(),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            pool_max_idle_per_host: usize::MAX,
+            headers: Vec::new(),
+            agent_segments: Vec::new(),
+            requester: None,
         }
     }
 
@@ -63,12 +142,216 @@ impl ClientBuilder {
         self
     }
 
+    /// max number of operations sent per request by
+    /// [batch_async](crate::Client::batch_async), defaults to 1000
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::client_builder::ClientBuilder;
+    /// let algolia_client_builder: ClientBuilder = ClientBuilder::new().set_batch_size(500);
+    /// ```
+    pub fn set_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// how [wait_task_async](crate::Client::wait_task_async) polls Algolia
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use EasyAlgolia::{
+    ///     client_builder::ClientBuilder,
+    ///     task::TaskWaitPolicy,
+    /// };
+    /// let algolia_client_builder: ClientBuilder =
+    ///     ClientBuilder::new().set_task_wait_policy(TaskWaitPolicy {
+    ///         timeout: Duration::from_secs(30),
+    ///         ..Default::default()
+    ///     });
+    /// ```
+    pub fn set_task_wait_policy(mut self, policy: TaskWaitPolicy) -> Self {
+        self.task_wait_policy = policy;
+        self
+    }
+
+    /// hosts requests are sent to and how failing hosts are retried, defaults to
+    /// [DefaultRetryStrategy](crate::retry::DefaultRetryStrategy) over the hosts of the
+    /// application
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use EasyAlgolia::{
+    ///     client_builder::ClientBuilder,
+    ///     retry::DefaultRetryStrategy,
+    /// };
+    /// let strategy = DefaultRetryStrategy::new("123").with_ttl(Duration::from_secs(30));
+    /// let algolia_client_builder: ClientBuilder = ClientBuilder::new().set_retry_strategy(strategy);
+    /// ```
+    pub fn set_retry_strategy(mut self, strategy: impl RetryStrategy + 'static) -> Self {
+        self.retry_strategy = Some(Arc::new(strategy));
+        self
+    }
+
+    /// send requests to the given hosts instead of the Algolia hosts of the application, such as
+    /// a local stand-in for Algolia or a proxy
+    /// hosts are tried in order by [DefaultRetryStrategy](crate::retry::DefaultRetryStrategy), a
+    /// retry strategy set with
+    /// [set_retry_strategy](crate::client_builder::ClientBuilder::set_retry_strategy) picks its
+    /// own hosts, setting both fails the build
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::{
+    ///     client_builder::ClientBuilder,
+    ///     retry::{
+    ///         Host,
+    ///         Scheme,
+    ///     },
+    /// };
+    /// let algolia_client_builder: ClientBuilder =
+    ///     ClientBuilder::new().hosts(vec![Host::new("localhost").scheme(Scheme::Http).port(8080)]);
+    /// ```
+    pub fn hosts(mut self, hosts: Vec<Host>) -> Self {
+        self.hosts = Some(hosts);
+        self
+    }
+
+    /// timeouts of the requests, see [Timeouts] for the defaults
+    /// a request timing out on a host is retried on the next one like any transport error
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use EasyAlgolia::client_builder::{
+    ///     ClientBuilder,
+    ///     Timeouts,
+    /// };
+    /// let algolia_client_builder: ClientBuilder = ClientBuilder::new().set_timeouts(Timeouts {
+    ///     write: Duration::from_secs(60),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// how long an idle connection is kept open for reuse, `None` keeps it forever
+    /// defaults to 90 seconds, ignored by requesters set with
+    /// [set_http_requester](crate::client_builder::ClientBuilder::set_http_requester)
+    pub fn set_pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// max number of idle connections kept open per host, unlimited by default
+    /// ignored by requesters set with
+    /// [set_http_requester](crate::client_builder::ClientBuilder::set_http_requester)
+    pub fn set_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = max_idle;
+        self
+    }
+
+    /// header sent with every request, on top of the Algolia credentials headers
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::client_builder::ClientBuilder;
+    /// let algolia_client_builder: ClientBuilder =
+    ///     ClientBuilder::new().add_header("X-Forwarded-For", "10.0.0.1");
+    /// ```
+    pub fn add_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    /// segment appended to the [AGENT] sent to Algolia, such as `MyApp (1.2.0)`
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::client_builder::ClientBuilder;
+    /// // agent sent is "EasyAlgolia/<version>; MyApp (1.2.0)"
+    /// let algolia_client_builder: ClientBuilder =
+    ///     ClientBuilder::new().add_agent_segment("MyApp (1.2.0)");
+    /// ```
+    pub fn add_agent_segment(mut self, segment: &str) -> Self {
+        self.agent_segments.push(String::from(segment));
+        self
+    }
+
+    /// http client sending the requests, reqwest is used by default
+    /// the connect timeout and pool settings only apply to the default requester, headers,
+    /// agent and read/write timeouts are given to any requester with each
+    /// [HttpRequest](crate::transport::HttpRequest)
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::{
+    ///     client_builder::ClientBuilder,
+    ///     transport::InMemoryRequester,
+    /// };
+    /// let algolia_client_builder: ClientBuilder =
+    ///     ClientBuilder::new().set_http_requester(InMemoryRequester::new());
+    /// ```
+    pub fn set_http_requester(mut self, requester: impl HttpRequester + 'static) -> Self {
+        self.requester = Some(Arc::new(requester));
+        self
+    }
+
+    /// agent and custom headers sent with every request
+    fn default_headers(&self) -> Result<HeaderMap, EasyAlgoliaError> {
+        let invalid_header = |name: &str| {
+            EasyAlgoliaError::new(
+                ErrorKind::ClientBuilderError,
+                Some(format!("invalid header {name}")),
+            )
+        };
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid_header(name))?,
+                HeaderValue::from_str(value).map_err(|_| invalid_header(name))?,
+            );
+        }
+        let agent = std::iter::once(AGENT)
+            .chain(self.agent_segments.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let agent = HeaderValue::from_str(&agent).map_err(|_| invalid_header("X-Algolia-Agent"))?;
+        headers.insert(USER_AGENT, agent.clone());
+        headers.insert("X-Algolia-Agent", agent);
+        Ok(headers)
+    }
+
+    /// requester set by the user, or the reqwest one holding the connection settings
+    fn requester(&self) -> Result<Arc<dyn HttpRequester>, EasyAlgoliaError> {
+        if let Some(requester) = &self.requester {
+            return Ok(Arc::clone(requester));
+        }
+        #[cfg(feature = "reqwest")]
+        {
+            let settings = crate::transport::HttpSettings {
+                connect_timeout: self.timeouts.connect,
+                pool_idle_timeout: self.pool_idle_timeout,
+                pool_max_idle_per_host: self.pool_max_idle_per_host,
+            };
+            crate::transport::ReqwestRequester::new(settings)
+                .map(|requester| Arc::new(requester) as Arc<dyn HttpRequester>)
+                .map_err(|err| {
+                    EasyAlgoliaError::new(
+                        ErrorKind::ClientBuilderError,
+                        Some(format!("failed to build http client: {err}")),
+                    )
+                })
+        }
+        #[cfg(not(feature = "reqwest"))]
+        Err(EasyAlgoliaError::new(
+            ErrorKind::ClientBuilderError,
+            Some("the reqwest feature is disabled, an http requester must be set".into()),
+        ))
+    }
+
     /// build the client from store credentials
     /// if api_key and app_id are set, function will consume them and set None
     /// returns error if either are not set
     /// # Examples
     /// ```
     /// use EasyAlgolia::client_builder::ClientBuilder;
+    /// # #[cfg(feature = "reqwest")]
     /// let mut algolia_client = ClientBuilder::new()
     ///     .set_application_id("123")
     ///     .set_api_key("123")
@@ -77,20 +360,52 @@ impl ClientBuilder {
     /// ```
     /// # Error
     /// returns [`Err(EasyAlgoliaError)`](crate::error::EasyAlgoliaError) if either of the
-    /// application_id or api_key is not set ```panics
+    /// application_id or api_key is not set, if the batch size is zero, if the hosts are empty or
+    /// set along with a retry strategy, if a header is invalid or if no http requester is
+    /// available ```panics
     /// use EasyAlgolia::client_builder::ClientBuilder ;
     /// // result in panic
     /// let mut algolia_client = ClientBuilder::new().build().unwrap();
     /// ```
-
-    pub fn build<'a>(&mut self) -> Result<Client, EasyAlgoliaError> {
+    pub fn build(&mut self) -> Result<Client, EasyAlgoliaError> {
+        if self.batch_size == 0 {
+            return Err(EasyAlgoliaError::new(
+                ErrorKind::ClientBuilderError,
+                Some("batch size must be greater than zero".into()),
+            ));
+        }
+        if self.hosts.as_ref().is_some_and(Vec::is_empty) {
+            return Err(EasyAlgoliaError::new(
+                ErrorKind::ClientBuilderError,
+                Some("at least one host must be set".into()),
+            ));
+        }
+        if self.hosts.is_some() && self.retry_strategy.is_some() {
+            return Err(EasyAlgoliaError::new(
+                ErrorKind::ClientBuilderError,
+                Some("hosts can not be set along with a retry strategy".into()),
+            ));
+        }
         if self.api_key.is_some() && self.application_id.is_some() {
+            let requester = self.requester()?;
+            let default_headers = self.default_headers()?;
             let api_key = mem::take(&mut self.api_key);
             let application_id = mem::take(&mut self.application_id);
-            return Ok(Client::new(
+            let mut client = Client::new(
                 api_key.unwrap().expose_secret(),
                 application_id.unwrap().expose_secret(),
-            ));
+                requester,
+            );
+            client.default_headers = default_headers;
+            client.timeouts = self.timeouts.clone();
+            client.batch_size = self.batch_size;
+            client.task_wait_policy = self.task_wait_policy.clone();
+            if let Some(strategy) = &self.retry_strategy {
+                client.retry_strategy = Arc::clone(strategy);
+            } else if let Some(hosts) = &self.hosts {
+                client.retry_strategy = Arc::new(DefaultRetryStrategy::with_hosts(hosts.clone()));
+            }
+            Ok(client)
         } else {
             Err(EasyAlgoliaError::new(
                 ErrorKind::ClientBuilderError,
@@ -108,8 +423,7 @@ impl ClientBuilder {
     /// ```ignore
     /// let mut algolia_client = ClientBuilder::build_from_env().unwrap();
     /// ```
-
-    pub fn build_from_env<'a>() -> Result<Client, EasyAlgoliaError> {
+    pub fn build_from_env() -> Result<Client, EasyAlgoliaError> {
         use std::env;
         let app_id = env::var("ALGOLIA_APPLICATION_ID").map_err(|_| {
             EasyAlgoliaError::new(ErrorKind::ClientBuilderError, Some("failed to fetch desireed Envviroment variables, ALGOLIA_APPLICATION_ID is not set. ".into()))
@@ -121,8 +435,9 @@ impl ClientBuilder {
             )
         })?;
 
-        let client = Client::new(&api_key, &app_id);
-
-        Ok(client)
+        Self::new()
+            .set_application_id(&app_id)
+            .set_api_key(&api_key)
+            .build()
     }
 }
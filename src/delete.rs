@@ -0,0 +1,170 @@
+//! Bulk deletes
+//! delete many documents at once, either by object id or by filters, see
+//! [client.delete_objects_async](crate::Client::delete_objects_async) and
+//! [client.delete_by_async](crate::Client::delete_by_async)
+use crate::{
+    algoliaobject::{
+        encode_params,
+        SearchQuery,
+    },
+    batch::BatchOperation,
+    error::{
+        EasyAlgoliaError,
+        ErrorKind,
+    },
+    request::AlgoliaRequest,
+    response::{
+        BatchResponse,
+        WriteResponse,
+    },
+    Client,
+    Index,
+    ObjectId,
+};
+use http::Method;
+
+/// params narrowing the documents deleted by query
+const FILTER_PARAMS: [&str; 4] = ["filters", "facetFilters", "numericFilters", "tagFilters"];
+
+/// params of a search accepted by a delete by query
+const DELETE_BY_PARAMS: [&str; 6] = [
+    "filters",
+    "facetFilters",
+    "numericFilters",
+    "tagFilters",
+    "aroundLatLng",
+    "aroundRadius",
+];
+
+/// params string of a delete by query, only the filters and the location of the query are used
+fn delete_by_params(filters: &SearchQuery) -> Result<String, EasyAlgoliaError> {
+    let mut params = filters.to_params()?;
+    params.retain(|name, _| DELETE_BY_PARAMS.contains(&name.as_str()));
+    // without filters every document would match, clearing the index must be explicit
+    if !FILTER_PARAMS.iter().any(|name| params.contains_key(*name)) {
+        return Err(EasyAlgoliaError::new(
+            ErrorKind::InvalidRequest,
+            Some("a filter must be set to delete documents by query".into()),
+        ));
+    }
+    Ok(encode_params(params))
+}
+
+fn delete_by_request(
+    index: &Index,
+    filters: &SearchQuery,
+) -> Result<AlgoliaRequest, EasyAlgoliaError> {
+    let params = delete_by_params(filters)?;
+    let path = format!("/1/indexes/{}/deleteByQuery", index.index());
+    AlgoliaRequest::write(Method::POST, path).json(&serde_json::json!({ "params": params }))
+}
+
+fn delete_operations(
+    object_ids: impl IntoIterator<Item = ObjectId>,
+) -> Vec<BatchOperation<ObjectId>> {
+    object_ids
+        .into_iter()
+        .map(BatchOperation::DeleteObject)
+        .collect()
+}
+
+impl Client {
+    /// delete many documents by object id
+    /// deletes are sent through the batch endpoint, chunked like
+    /// [batch_async](crate::Client::batch_async)
+    /// # Examples
+    /// ```ignore
+    ///    let ids: Vec<ObjectId> = vec!["halo".into(), "doom".into()];
+    ///    client.delete_objects_async(&index, ids).await?;
+    /// ```
+    pub async fn delete_objects_async(
+        &self,
+        index: &Index,
+        object_ids: impl IntoIterator<Item = ObjectId>,
+    ) -> Result<BatchResponse, EasyAlgoliaError> {
+        self.batch_async(index, delete_operations(object_ids)).await
+    }
+
+    /// same as [delete_objects_async](crate::Client::delete_objects_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn delete_objects(
+        &self,
+        index: &Index,
+        object_ids: impl IntoIterator<Item = ObjectId>,
+    ) -> Result<BatchResponse, EasyAlgoliaError> {
+        self.batch(index, delete_operations(object_ids))
+    }
+
+    /// delete every document matching the filters of `filters`
+    /// the filter built with [SearchQuery::filter](crate::algoliaobject::SearchQuery::filter)
+    /// and the facet, numeric and tag filter arrays all have to match, optional filters, the text
+    /// query and the other search params are ignored
+    /// # Examples
+    /// ```ignore
+    ///    let filters = SearchQuery::new().filter("tenant:42");
+    ///    let response = client.delete_by_async(&index, filters).await?;
+    ///    client.wait_task_async(&index, response.task_id).await?;
+    /// ```
+    /// # Error
+    /// returns [`Err(EasyAlgoliaError)`](crate::error::EasyAlgoliaError) if no filter is set or
+    /// the filters are invalid, use [clear_index_async](crate::Client::clear_index_async) to delete
+    /// every document
+    pub async fn delete_by_async(
+        &self,
+        index: &Index,
+        filters: SearchQuery,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(delete_by_request(index, &filters)?).await
+    }
+
+    /// same as [delete_by_async](crate::Client::delete_by_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn delete_by(
+        &self,
+        index: &Index,
+        filters: SearchQuery,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(delete_by_request(index, &filters)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filter::Filter;
+
+    #[test]
+    fn test_delete_by_params() {
+        let filters = SearchQuery::new()
+            .query("ignored")
+            .filter("tenant:42")
+            .and_filter("archived:true");
+        assert_eq!(
+            delete_by_params(&filters).unwrap(),
+            "filters=tenant%3A42+AND+archived%3Atrue"
+        );
+        let filters = SearchQuery::new()
+            .filter("tenant:42")
+            .facet_filters(Filter::eq("genre", "rpg"))
+            .hits_per_page(10);
+        assert_eq!(
+            delete_by_params(&filters).unwrap(),
+            "facetFilters=%5B%22genre%3Arpg%22%5D&filters=tenant%3A42"
+        );
+    }
+
+    #[test]
+    fn test_delete_by_requires_filter() {
+        let filters = SearchQuery::new().query("everything");
+        assert!(delete_by_params(&filters).is_err());
+        // optional filters only rank the hits, they match every document
+        let filters = SearchQuery::new().optional_filters(Filter::eq("genre", "rpg"));
+        assert!(delete_by_params(&filters).is_err());
+        let filters = SearchQuery::new().facet_filters(Filter::gt("price", 10));
+        assert!(delete_by_params(&filters).is_err());
+        let filters = SearchQuery::new().filter(Filter::any(Vec::<Filter>::new()));
+        assert!(delete_by_params(&filters).is_err());
+    }
+}
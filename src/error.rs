@@ -1,71 +1,271 @@
-///! Error from EasyAlgolia
+//! Error from EasyAlgolia
+use crate::transport::{
+    HttpError,
+    HttpErrorKind,
+};
 use core::fmt;
 use std::error::Error;
 
-#[derive(Debug)]
+/// category of an [EasyAlgoliaError]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorKind {
+    /// request failed for a reason not covered by the other kinds
     RequestError,
     ClientBuilderError,
     ClientError,
+    /// the index or document does not exist, http 404
+    NotFound,
+    /// the api key is invalid or lacks the rights for the operation, http 401 and 403
+    Unauthorized,
+    /// too many requests were sent, http 429
+    RateLimited,
+    /// Algolia refused the request, any other http 4xx, or the request could not be built
+    InvalidRequest,
+    /// Algolia failed to process the request, http 5xx
+    ServerError,
+    /// the request or the wait for a task took too long
+    Timeout,
+    /// the response body is not what was expected
+    Decode,
+    /// Algolia could not be reached, dns, connection or tls failure
+    Transport,
 }
 
+impl ErrorKind {
+    /// kind matching a non success http status sent by Algolia
+    pub(crate) fn from_status(status: u16) -> Self {
+        match status {
+            404 => Self::NotFound,
+            401 | 403 => Self::Unauthorized,
+            429 => Self::RateLimited,
+            400..=499 => Self::InvalidRequest,
+            500..=599 => Self::ServerError,
+            _ => Self::RequestError,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::RequestError => "request failed",
+            Self::ClientBuilderError => "failed to build client",
+            Self::ClientError => "client error",
+            Self::NotFound => "not found",
+            Self::Unauthorized => "unauthorized",
+            Self::RateLimited => "rate limited",
+            Self::InvalidRequest => "invalid request",
+            Self::ServerError => "Algolia server error",
+            Self::Timeout => "timed out",
+            Self::Decode => "failed to decode response",
+            Self::Transport => "failed to reach Algolia",
+        };
+        f.write_str(description)
+    }
+}
+
+/// error returned by every fallible EasyAlgolia operation
+/// errors coming from Algolia carry the http status and the message Algolia sent, errors coming
+/// from the http client or from json decoding keep the original error as
+/// [source](std::error::Error::source)
+/// ```ignore
+///    match client.search_async::<Game>(&index, query).await {
+///        Err(err) if err.kind() == ErrorKind::RateLimited => retry_later(),
+///        Err(err) => eprintln!("search failed: {err}"),
+///        Ok(response) => show(response),
+///    }
+/// ```
 pub struct EasyAlgoliaError {
-    error_kind: ErrorKind,
-    cause: Option<String>,
+    kind: ErrorKind,
+    status: Option<u16>,
+    message: Option<String>,
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
 }
 
 impl fmt::Debug for EasyAlgoliaError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Write custom formatting logic here
-        match self.error_kind {
-            ErrorKind::RequestError => {
-                if let Some(cause) = self.cause.as_ref() {
-                    write!(
-                        f,
-                        "EasyAlgoliaError {{ error_kind: {:?}, cause: {:?} }}",
-                        self.error_kind,
-                        cause.replace("\"", "")
-                    )
-                } else {
-                    write!(f, "EasyAlgoliaError {{ error_kind: {:?}}}", self.error_kind)
-                }
-            }
-            _ => {
-                write!(
-                    f,
-                    "EasyAlgoliaError {{ error_kind: {:?}, cause: {:?} }}",
-                    self.error_kind, self.cause
-                )
-            }
+        let mut debug = f.debug_struct("EasyAlgoliaError");
+        debug.field("kind", &self.kind);
+        if let Some(status) = self.status {
+            debug.field("status", &status);
+        }
+        if let Some(message) = &self.message {
+            debug.field("message", message);
         }
+        if let Some(source) = &self.source {
+            debug.field("source", source);
+        }
+        debug.finish()
     }
 }
 
+impl From<HttpError> for EasyAlgoliaError {
+    fn from(err: HttpError) -> Self {
+        let kind = match err.kind() {
+            HttpErrorKind::Connect | HttpErrorKind::Other => ErrorKind::Transport,
+            HttpErrorKind::Timeout => ErrorKind::Timeout,
+            HttpErrorKind::InvalidRequest => ErrorKind::InvalidRequest,
+        };
+        Self {
+            kind,
+            status: None,
+            message: None,
+            source: Some(err.into_source()),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
 impl From<reqwest::Error> for EasyAlgoliaError {
-    fn from(_: reqwest::Error) -> Self {
-        EasyAlgoliaError::new(ErrorKind::RequestError, None)
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if err.is_timeout() {
+            ErrorKind::Timeout
+        } else if err.is_decode() {
+            ErrorKind::Decode
+        } else if let Some(status) = err.status() {
+            ErrorKind::from_status(status.as_u16())
+        } else if err.is_builder() {
+            ErrorKind::InvalidRequest
+        } else {
+            ErrorKind::Transport
+        };
+        Self {
+            kind,
+            status: err.status().map(|status| status.as_u16()),
+            message: None,
+            source: Some(Box::new(err)),
+        }
     }
 }
-impl<'a> EasyAlgoliaError {
-    pub(crate) fn new(error_kind: ErrorKind, cause: Option<String>) -> Self {
-        match error_kind {
-            ErrorKind::ClientBuilderError => Self { error_kind, cause },
-            ErrorKind::RequestError => Self { error_kind, cause },
-            ErrorKind::ClientError => Self { error_kind, cause },
+
+impl From<serde_json::Error> for EasyAlgoliaError {
+    fn from(err: serde_json::Error) -> Self {
+        Self {
+            kind: ErrorKind::Decode,
+            status: None,
+            message: None,
+            source: Some(Box::new(err)),
         }
     }
 }
 
-impl<'a> fmt::Display for EasyAlgoliaError {
+impl EasyAlgoliaError {
+    pub(crate) fn new(kind: ErrorKind, message: Option<String>) -> Self {
+        Self {
+            kind,
+            status: None,
+            message,
+            source: None,
+        }
+    }
+
+    /// error for a non success response, Algolia sends `{"message": "...", "status": 404}`
+    /// bodies, anything else is kept as is in the message
+    pub(crate) fn from_response(status: u16, body: &str) -> Self {
+        #[derive(serde::Deserialize)]
+        struct AlgoliaErrorBody {
+            message: String,
+        }
+        let message = match serde_json::from_str::<AlgoliaErrorBody>(body) {
+            Ok(error) => Some(error.message),
+            Err(_) if body.trim().is_empty() => None,
+            Err(_) => Some(body.trim().into()),
+        };
+        Self {
+            kind: ErrorKind::from_status(status),
+            status: Some(status),
+            message,
+            source: None,
+        }
+    }
+
+    /// category of the error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// http status sent by Algolia, `None` if the error did not come from a response
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// message explaining the error, for Algolia errors this is the message sent by Algolia
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+impl fmt::Display for EasyAlgoliaError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Implement formatting of your error message here
-        write!(f, "An error occurred in EasyAlgolia: ...")
+        write!(f, "{}", self.kind)?;
+        match (&self.message, &self.source) {
+            (Some(message), _) => write!(f, ": {message}")?,
+            (None, Some(source)) => write!(f, ": {source}")?,
+            (None, None) => {}
+        }
+        if let Some(status) = self.status {
+            write!(f, " (http status {status})")?;
+        }
+        Ok(())
     }
 }
 
 impl Error for EasyAlgoliaError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        // Optionally provide the underlying cause of the error
-        None
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_from_algolia_response() {
+        let err = EasyAlgoliaError::from_response(
+            404,
+            r#"{"message":"Index does not exist","status":404}"#,
+        );
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.status(), Some(404));
+        assert_eq!(err.message(), Some("Index does not exist"));
+        assert_eq!(
+            err.to_string(),
+            "not found: Index does not exist (http status 404)"
+        );
+    }
+
+    #[test]
+    fn test_error_kind_from_status() {
+        let kinds: Vec<ErrorKind> = [400, 401, 403, 429, 500, 503]
+            .into_iter()
+            .map(|status| EasyAlgoliaError::from_response(status, "").kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::InvalidRequest,
+                ErrorKind::Unauthorized,
+                ErrorKind::Unauthorized,
+                ErrorKind::RateLimited,
+                ErrorKind::ServerError,
+                ErrorKind::ServerError,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_keeps_raw_body_and_source() {
+        let err = EasyAlgoliaError::from_response(502, "<html>bad gateway</html>\n");
+        assert_eq!(err.message(), Some("<html>bad gateway</html>"));
+        assert!(err.source().is_none());
+
+        let json_err = serde_json::from_str::<u32>("{").unwrap_err();
+        let err: EasyAlgoliaError = json_err.into();
+        assert_eq!(err.kind(), ErrorKind::Decode);
+        assert!(err.source().is_some());
+        assert!(err.to_string().starts_with("failed to decode response: "));
     }
 }
@@ -0,0 +1,940 @@
+//! Typed search filters
+//! a [Filter] is an expression tree rendered to the Algolia filter syntax, values are quoted and
+//! escaped and groups are parenthesized, so the filter sent is the one that was built
+//! # Usage
+//! ```
+//! use EasyAlgolia::filter::Filter;
+//! let filter = Filter::eq("platform", "PlayStation 5")
+//!     .or(Filter::eq("platform", "PC"))
+//!     .and(Filter::ge("price", 10).and(Filter::tag("multiplayer")))
+//!     .and(!Filter::eq("available", false));
+//! assert_eq!(
+//!     filter.to_string(),
+//!     r#"(platform:"PlayStation 5" OR platform:"PC") AND price >= 10 AND _tags:"multiplayer" AND NOT available:false"#
+//! );
+//! ```
+//! the same filters fill the `facetFilters`, `numericFilters`, `tagFilters` and `optionalFilters`
+//! arrays, see [FilterArray]
+use crate::{
+    algoliaobject::AttributeName,
+    error::{
+        EasyAlgoliaError,
+        ErrorKind,
+    },
+};
+use serde_json::Value;
+use std::fmt;
+
+/// number compared by a numeric filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterNumber {
+    Integer(i64),
+    Float(f64),
+}
+
+impl fmt::Display for FilterNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(number) => write!(f, "{number}"),
+            Self::Float(number) => write!(f, "{number}"),
+        }
+    }
+}
+
+/// value compared by [Filter::eq] and [Filter::ne]
+/// text is matched against a facet, booleans against a boolean facet and numbers with a numeric
+/// comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Text(String),
+    Bool(bool),
+    Number(FilterNumber),
+}
+
+/// value as written in the filter arrays, text is not quoted
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(text),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(number) => write!(f, "{number}"),
+        }
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&String> for FilterValue {
+    fn from(value: &String) -> Self {
+        Self::Text(value.clone())
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+macro_rules! filter_number_from {
+    ($variant:ident: $($number:ty),*) => {
+        $(impl From<$number> for FilterNumber {
+            fn from(value: $number) -> Self {
+                Self::$variant(value.into())
+            }
+        })*
+        $(impl From<$number> for FilterValue {
+            fn from(value: $number) -> Self {
+                Self::Number(value.into())
+            }
+        })*
+    };
+}
+
+filter_number_from!(Integer: i8, i16, i32, i64, u8, u16, u32);
+filter_number_from!(Float: f32, f64);
+
+impl From<u64> for FilterNumber {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Self::Float(value as f64), Self::Integer)
+    }
+}
+
+impl From<usize> for FilterNumber {
+    fn from(value: usize) -> Self {
+        Self::from(value as u64)
+    }
+}
+
+impl From<isize> for FilterNumber {
+    fn from(value: isize) -> Self {
+        Self::Integer(value as i64)
+    }
+}
+
+macro_rules! filter_value_from_number {
+    ($($number:ty),*) => {
+        $(impl From<$number> for FilterValue {
+            fn from(value: $number) -> Self {
+                Self::Number(value.into())
+            }
+        })*
+    };
+}
+
+filter_value_from_number!(u64, usize, isize);
+
+/// operator of a numeric comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    /// the comparison matching every number this one does not
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Eq => Self::Ne,
+            Self::Ne => Self::Eq,
+            Self::Ge => Self::Lt,
+            Self::Gt => Self::Le,
+        }
+    }
+
+    fn operator(self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Ge => ">=",
+            Self::Gt => ">",
+        }
+    }
+}
+
+/// filter expression of a search, see the [module](self) documentation
+/// combine filters with [and](Filter::and), [or](Filter::or), [all](Filter::all),
+/// [any](Filter::any) and negate them with `!`
+/// plain strings convert to [Filter::Raw] and are sent as written
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// `attribute:"value"`, `attribute:true` or `attribute:10`
+    Facet {
+        attribute: String,
+        value: FilterValue,
+    },
+    /// `attribute >= 10`
+    Numeric {
+        attribute: String,
+        comparison: Comparison,
+        value: FilterNumber,
+    },
+    /// `attribute:10 TO 20`, both bounds included
+    Range {
+        attribute: String,
+        low: FilterNumber,
+        high: FilterNumber,
+    },
+    /// `_tags:"value"`
+    Tag(String),
+    /// filter in the Algolia syntax, sent as is
+    Raw(String),
+    /// `NOT filter`
+    Not(Box<Filter>),
+    /// `filter<score=3>`, documents matching filters with a higher score rank first
+    Scored { filter: Box<Filter>, score: u32 },
+    /// every filter matches, an empty group matches every document
+    And(Vec<Filter>),
+    /// at least one filter matches, an empty group matches no document
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// documents whose attribute equals `value`, text and booleans are matched against a facet,
+    /// numbers are compared
+    pub fn eq(attribute: impl AttributeName, value: impl Into<FilterValue>) -> Self {
+        let attribute = attribute.attribute_name().into();
+        match value.into() {
+            FilterValue::Number(value) => Self::Numeric {
+                attribute,
+                comparison: Comparison::Eq,
+                value,
+            },
+            value => Self::Facet { attribute, value },
+        }
+    }
+
+    /// documents whose attribute differs from `value`
+    pub fn ne(attribute: impl AttributeName, value: impl Into<FilterValue>) -> Self {
+        !Self::eq(attribute, value)
+    }
+
+    /// documents whose attribute is lower than `value`
+    pub fn lt(attribute: impl AttributeName, value: impl Into<FilterNumber>) -> Self {
+        Self::numeric(attribute, Comparison::Lt, value)
+    }
+
+    /// documents whose attribute is lower than or equal to `value`
+    pub fn le(attribute: impl AttributeName, value: impl Into<FilterNumber>) -> Self {
+        Self::numeric(attribute, Comparison::Le, value)
+    }
+
+    /// documents whose attribute is greater than or equal to `value`
+    pub fn ge(attribute: impl AttributeName, value: impl Into<FilterNumber>) -> Self {
+        Self::numeric(attribute, Comparison::Ge, value)
+    }
+
+    /// documents whose attribute is greater than `value`
+    pub fn gt(attribute: impl AttributeName, value: impl Into<FilterNumber>) -> Self {
+        Self::numeric(attribute, Comparison::Gt, value)
+    }
+
+    /// documents whose attribute compares to `value`
+    pub fn numeric(
+        attribute: impl AttributeName,
+        comparison: Comparison,
+        value: impl Into<FilterNumber>,
+    ) -> Self {
+        Self::Numeric {
+            attribute: attribute.attribute_name().into(),
+            comparison,
+            value: value.into(),
+        }
+    }
+
+    /// documents whose attribute is between `low` and `high`, both included
+    pub fn range(
+        attribute: impl AttributeName,
+        low: impl Into<FilterNumber>,
+        high: impl Into<FilterNumber>,
+    ) -> Self {
+        Self::Range {
+            attribute: attribute.attribute_name().into(),
+            low: low.into(),
+            high: high.into(),
+        }
+    }
+
+    /// documents tagged with `tag` in their `_tags` attribute
+    pub fn tag(tag: &str) -> Self {
+        Self::Tag(tag.into())
+    }
+
+    /// filter written in the Algolia syntax, sent as is
+    pub fn raw(filter: &str) -> Self {
+        Self::Raw(filter.into())
+    }
+
+    /// rank documents matching the filter by `score`, only facet and tag filters take a score
+    pub fn score(self, score: u32) -> Self {
+        Self::Scored {
+            filter: Box::new(self),
+            score,
+        }
+    }
+
+    /// documents matching every filter
+    pub fn all(filters: impl IntoIterator<Item = impl Into<Filter>>) -> Self {
+        filters
+            .into_iter()
+            .fold(Self::And(Vec::new()), |all, filter| all.and(filter))
+    }
+
+    /// documents matching at least one of the filters
+    /// without filters the group matches no document, Algolia has no filter for that so
+    /// [validate](Filter::validate) refuses it
+    pub fn any(filters: impl IntoIterator<Item = impl Into<Filter>>) -> Self {
+        let mut filters = filters.into_iter().map(Into::into);
+        let first = filters.next().unwrap_or(Self::Or(Vec::new()));
+        filters.fold(first, |any, filter| any.or(filter))
+    }
+
+    /// documents matching both filters, nested groups of the same operator are flattened
+    pub fn and(self, filter: impl Into<Filter>) -> Self {
+        Self::And(
+            [self, filter.into()]
+                .into_iter()
+                .flat_map(|filter| match filter {
+                    Self::And(filters) => filters,
+                    filter => vec![filter],
+                })
+                .collect(),
+        )
+    }
+
+    /// documents matching either filter, nested groups of the same operator are flattened
+    pub fn or(self, filter: impl Into<Filter>) -> Self {
+        Self::Or(
+            [self, filter.into()]
+                .into_iter()
+                .flat_map(|filter| match filter {
+                    Self::Or(filters) => filters,
+                    filter => vec![filter],
+                })
+                .collect(),
+        )
+    }
+
+    /// a group of a single filter is the filter itself
+    fn simplify(self) -> Self {
+        match self {
+            Self::And(mut filters) | Self::Or(mut filters) if filters.len() == 1 => {
+                filters.remove(0)
+            }
+            filter => filter,
+        }
+    }
+
+    /// the De Morgan `expanded` negation of `group` when Algolia accepts it, `NOT group` otherwise
+    fn negate_group(expanded: Self, group: Self) -> Self {
+        match expanded.validate() {
+            Ok(()) => expanded,
+            Err(_) => Self::Not(Box::new(group)),
+        }
+    }
+
+    /// `true` when the filter matches every document and renders to an empty string
+    /// an OR group matches every document as soon as one of its filters does
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::And(filters) => filters.iter().all(Self::is_empty),
+            Self::Or(filters) => filters.iter().any(Self::is_empty),
+            Self::Raw(filter) => filter.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    /// kind of the filter for the OR groups of the filter syntax, `true` for numeric filters
+    fn is_numeric(&self) -> Option<bool> {
+        match self {
+            Self::Facet { .. } | Self::Tag(_) => Some(false),
+            Self::Numeric { .. } | Self::Range { .. } => Some(true),
+            Self::Not(filter) | Self::Scored { filter, .. } => filter.is_numeric(),
+            Self::Raw(_) | Self::And(_) | Self::Or(_) => None,
+        }
+    }
+
+    /// check Algolia accepts the filter in the `filters` param
+    /// Algolia refuses AND groups inside OR groups, OR groups mixing numeric filters with facet
+    /// or tag filters, negated groups and scores on anything but facet and tag filters, raw
+    /// filters are not checked unless they are negated
+    pub fn validate(&self) -> Result<(), EasyAlgoliaError> {
+        self.validate_in(false)
+    }
+
+    fn validate_in(&self, in_or: bool) -> Result<(), EasyAlgoliaError> {
+        match self {
+            Self::And(filters) => {
+                if in_or && filters.iter().filter(|filter| !filter.is_empty()).count() > 1 {
+                    return Err(invalid_filter(format!(
+                        "an AND group can not be nested in an OR group: {self}"
+                    )));
+                }
+                filters
+                    .iter()
+                    .try_for_each(|filter| filter.validate_in(in_or))
+            }
+            Self::Or(filters) if filters.is_empty() => Err(invalid_filter(
+                "an OR group must hold at least one filter, an empty group matches no document"
+                    .into(),
+            )),
+            Self::Or(filters) => {
+                let mut kinds = filters.iter().filter_map(Self::is_numeric);
+                if let Some(numeric) = kinds.next() {
+                    if kinds.any(|kind| kind != numeric) {
+                        return Err(invalid_filter(format!(
+                            "numeric filters can not be in an OR group with facet or tag \
+                             filters: {self}"
+                        )));
+                    }
+                }
+                filters
+                    .iter()
+                    .try_for_each(|filter| filter.validate_in(true))
+            }
+            Self::Not(filter) => match **filter {
+                Self::And(_) | Self::Or(_) | Self::Not(_) | Self::Scored { .. } => Err(
+                    invalid_filter(format!("NOT only applies to a single filter: {self}")),
+                ),
+                Self::Raw(_) if filter.is_grouped() => Err(invalid_filter(format!(
+                    "NOT only applies to a single filter: {self}"
+                ))),
+                _ => Ok(()),
+            },
+            Self::Scored { filter, .. } => match **filter {
+                Self::Facet { .. } | Self::Tag(_) | Self::Raw(_) => Ok(()),
+                _ => Err(invalid_filter(format!(
+                    "only facet and tag filters take a score: {self}"
+                ))),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// the filter as the json array of a filter array param
+    /// the filter is an AND of filters or OR groups of filters, such as
+    /// `(a OR b) AND c` sent as `[["a", "b"], "c"]`
+    /// # Usage
+    /// ```
+    /// use EasyAlgolia::filter::{
+    ///     Filter,
+    ///     FilterArray,
+    /// };
+    /// let filter = Filter::eq("genre", "rpg")
+    ///     .or(Filter::eq("genre", "action").score(2))
+    ///     .and(Filter::ne("platform", "pc"));
+    /// assert_eq!(
+    ///     filter.to_array(FilterArray::Optional).unwrap(),
+    ///     serde_json::json!([["genre:rpg", "genre:action<score=2>"], "platform:-pc"])
+    /// );
+    /// // numeric filters belong in numericFilters
+    /// assert!(Filter::gt("price", 10)
+    ///     .to_array(FilterArray::Facet)
+    ///     .is_err());
+    /// ```
+    pub fn to_array(&self, array: FilterArray) -> Result<Value, EasyAlgoliaError> {
+        let groups = match self {
+            Self::And(filters) => filters.iter().collect(),
+            filter => vec![filter],
+        };
+        groups
+            .into_iter()
+            .filter(|filter| !filter.is_empty())
+            .map(|filter| match filter {
+                Self::Or(filters) if filters.is_empty() => Err(invalid_filter(format!(
+                    "the OR groups of {} can not be empty",
+                    array.param()
+                ))),
+                Self::Or(filters) => filters
+                    .iter()
+                    .map(|filter| filter.array_element(array).map(Value::String))
+                    .collect(),
+                filter => filter.array_element(array).map(Value::String),
+            })
+            .collect()
+    }
+
+    /// single filter of a filter array param
+    fn array_element(&self, array: FilterArray) -> Result<String, EasyAlgoliaError> {
+        let facets = matches!(array, FilterArray::Facet | FilterArray::Optional);
+        match (self, array) {
+            (Self::Raw(filter), _) => Ok(filter.clone()),
+            (Self::And(filters) | Self::Or(filters), _) if filters.len() == 1 => {
+                filters[0].array_element(array)
+            }
+            (Self::Scored { filter, score }, FilterArray::Optional) => {
+                Ok(format!("{}<score={score}>", filter.array_element(array)?))
+            }
+            (Self::Facet { attribute, value }, _) if facets => Ok(format!(
+                "{attribute}:{}",
+                escape_negation(&value.to_string())
+            )),
+            (Self::Tag(tag), _) if facets => Ok(format!("_tags:{}", escape_negation(tag))),
+            (Self::Tag(tag), FilterArray::Tag) => Ok(escape_negation(tag)),
+            (Self::Not(filter), _) => match (&**filter, array) {
+                (Self::Facet { attribute, value }, _) if facets => Ok(format!(
+                    "{attribute}:-{}",
+                    escape_negation(&value.to_string())
+                )),
+                (Self::Tag(tag), _) if facets => Ok(format!("_tags:-{}", escape_negation(tag))),
+                (Self::Tag(tag), FilterArray::Tag) => Ok(format!("-{}", escape_negation(tag))),
+                _ => Err(self.unsupported_in(array)),
+            },
+            (
+                Self::Numeric {
+                    attribute,
+                    comparison,
+                    value,
+                },
+                FilterArray::Numeric,
+            ) => Ok(format!("{attribute} {} {value}", comparison.operator())),
+            (Self::Range { .. }, FilterArray::Numeric) => Ok(self.to_string()),
+            _ => Err(self.unsupported_in(array)),
+        }
+    }
+
+    fn unsupported_in(&self, array: FilterArray) -> EasyAlgoliaError {
+        invalid_filter(format!("{} can not hold {self}", array.param()))
+    }
+
+    /// `true` when the filter joins several filters with `AND` or `OR`
+    fn is_grouped(&self) -> bool {
+        match self {
+            Self::And(filters) | Self::Or(filters) => filters.len() > 1,
+            Self::Raw(filter) => [" AND ", " OR "]
+                .iter()
+                .any(|operator| filter.contains(operator)),
+            _ => false,
+        }
+    }
+
+    /// write the filter as an operand of a group or a negation, wrapping it in parentheses when
+    /// it would otherwise bind to its neighbours
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_grouped() {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+
+    fn fmt_group(filters: &[Filter], operator: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut filters = filters.iter().filter(|filter| !filter.is_empty());
+        if let Some(first) = filters.next() {
+            first.fmt_operand(f)?;
+        }
+        filters.try_for_each(|filter| {
+            f.write_str(operator)?;
+            filter.fmt_operand(f)
+        })
+    }
+}
+
+/// negate a filter, a single filter is written `NOT filter` so documents without the attribute
+/// still match
+/// Algolia only negates single filters, a negated group is expanded with De Morgan's laws when
+/// the expansion is a valid filter and is otherwise kept for [validate](Filter::validate) to
+/// refuse
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output {
+        match self.simplify() {
+            Self::Not(filter) => *filter,
+            Self::And(filters) => Self::negate_group(
+                Self::any(filters.iter().cloned().map(|filter| !filter)),
+                Self::And(filters),
+            ),
+            Self::Or(filters) => Self::negate_group(
+                Self::all(filters.iter().cloned().map(|filter| !filter)),
+                Self::Or(filters),
+            ),
+            filter => Self::Not(Box::new(filter)),
+        }
+    }
+}
+
+/// param of a search holding filters as json arrays instead of the filter syntax
+/// the top level array is an AND, nested arrays are OR groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterArray {
+    /// `facetFilters`, facet and tag filters such as `genre:rpg` or `genre:-rpg`
+    Facet,
+    /// `numericFilters`, comparisons and ranges such as `price > 10`
+    Numeric,
+    /// `tagFilters`, tags such as `beta` or `-beta`
+    Tag,
+    /// `optionalFilters`, facet and tag filters ranking the documents matching them first, with
+    /// an optional score
+    Optional,
+}
+
+impl FilterArray {
+    /// name of the search param
+    pub fn param(self) -> &'static str {
+        match self {
+            Self::Facet => "facetFilters",
+            Self::Numeric => "numericFilters",
+            Self::Tag => "tagFilters",
+            Self::Optional => "optionalFilters",
+        }
+    }
+}
+
+fn invalid_filter(message: String) -> EasyAlgoliaError {
+    EasyAlgoliaError::new(ErrorKind::InvalidRequest, Some(message))
+}
+
+/// a leading `-` negates a filter array value, it is escaped when part of the value
+fn escape_negation(value: &str) -> String {
+    if value.starts_with('-') {
+        format!("\\{value}")
+    } else {
+        value.into()
+    }
+}
+
+/// write `value` between double quotes, escaping quotes and backslashes
+fn quote(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    f.write_str("\"")
+}
+
+/// attribute names are quoted unless they only hold characters the filter syntax reads as a name
+fn attribute(f: &mut fmt::Formatter<'_>, attribute: &str) -> fmt::Result {
+    let bare = !attribute.is_empty()
+        && attribute
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        && !["AND", "OR", "NOT", "TO"].contains(&attribute);
+    if bare {
+        f.write_str(attribute)
+    } else {
+        quote(f, attribute)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Facet {
+                attribute: name,
+                value,
+            } => {
+                attribute(f, name)?;
+                f.write_str(":")?;
+                match value {
+                    FilterValue::Text(text) => quote(f, text),
+                    FilterValue::Bool(value) => write!(f, "{value}"),
+                    FilterValue::Number(number) => write!(f, "{number}"),
+                }
+            }
+            Self::Numeric {
+                attribute: name,
+                comparison,
+                value,
+            } => {
+                attribute(f, name)?;
+                write!(f, " {} {value}", comparison.operator())
+            }
+            Self::Range {
+                attribute: name,
+                low,
+                high,
+            } => {
+                attribute(f, name)?;
+                write!(f, ":{low} TO {high}")
+            }
+            Self::Tag(tag) => {
+                f.write_str("_tags:")?;
+                quote(f, tag)
+            }
+            Self::Raw(filter) => f.write_str(filter),
+            Self::Not(filter) => {
+                f.write_str("NOT ")?;
+                filter.fmt_operand(f)
+            }
+            Self::Scored { filter, score } => {
+                filter.fmt_operand(f)?;
+                write!(f, "<score={score}>")
+            }
+            // a group matching every document is left out, like an empty filter
+            Self::And(_) | Self::Or(_) if self.is_empty() => Ok(()),
+            Self::And(filters) => Self::fmt_group(filters, " AND ", f),
+            Self::Or(filters) => Self::fmt_group(filters, " OR ", f),
+        }
+    }
+}
+
+impl From<&str> for Filter {
+    fn from(filter: &str) -> Self {
+        Self::raw(filter)
+    }
+}
+
+impl From<String> for Filter {
+    fn from(filter: String) -> Self {
+        Self::Raw(filter)
+    }
+}
+
+impl From<&String> for Filter {
+    fn from(filter: &String) -> Self {
+        Self::raw(filter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_values_are_quoted() {
+        assert_eq!(
+            Filter::eq("brand", r#"Tom & "Jerry" | C:\"#).to_string(),
+            r#"brand:"Tom & \"Jerry\" | C:\\""#
+        );
+        assert_eq!(Filter::tag("a OR b").to_string(), r#"_tags:"a OR b""#);
+        assert_eq!(
+            Filter::eq("release year", 2020).to_string(),
+            r#""release year" = 2020"#
+        );
+        assert_eq!(
+            Filter::eq("meta.is_new", true).to_string(),
+            "meta.is_new:true"
+        );
+    }
+
+    #[test]
+    fn test_numeric_filters() {
+        assert_eq!(Filter::lt("price", 10).to_string(), "price < 10");
+        assert_eq!(Filter::le("price", 9.5).to_string(), "price <= 9.5");
+        assert_eq!(Filter::gt("price", 10u64).to_string(), "price > 10");
+        assert_eq!(Filter::ge("stock", 0).to_string(), "stock >= 0");
+        assert_eq!(Filter::ne("price", 10).to_string(), "NOT price = 10");
+        assert_eq!(
+            Filter::range("year", 2010, 2020).to_string(),
+            "year:2010 TO 2020"
+        );
+    }
+
+    #[test]
+    fn test_filter_groups() {
+        let filter = Filter::eq("genre", "rpg")
+            .or(Filter::eq("genre", "action"))
+            .and(Filter::gt("price", 10))
+            .and(Filter::eq("available", true).or(Filter::tag("preorder")));
+        assert_eq!(
+            filter.to_string(),
+            r#"(genre:"rpg" OR genre:"action") AND price > 10 AND (available:true OR _tags:"preorder")"#
+        );
+        assert_eq!(
+            Filter::any(["a:1", "b:2 AND c:3"]).to_string(),
+            "a:1 OR (b:2 AND c:3)"
+        );
+        assert_eq!(Filter::all(["a:1"]), Filter::And(vec![Filter::raw("a:1")]));
+        assert_eq!(Filter::all(["a:1"]).to_string(), "a:1");
+        assert!(Filter::all(Vec::<Filter>::new()).is_empty());
+        assert_eq!(
+            Filter::all([Filter::all(Vec::<Filter>::new()), Filter::raw("a:1")]).to_string(),
+            "a:1"
+        );
+    }
+
+    #[test]
+    fn test_empty_groups() {
+        let nothing = Filter::any(Vec::<Filter>::new());
+        assert_eq!(nothing, Filter::Or(Vec::new()));
+        assert!(!nothing.is_empty());
+        assert!(nothing.validate().is_err());
+        // an empty OR group nested in a filter still matches no document
+        let filter = Filter::eq("a", "b").and(nothing.clone());
+        assert!(filter.validate().is_err());
+        assert!((!Filter::all(Vec::<Filter>::new())).validate().is_err());
+        assert!(nothing.to_array(FilterArray::Facet).is_err());
+
+        // an OR group holding an empty AND group matches every document
+        let everything = Filter::Or(vec![Filter::all(Vec::<Filter>::new()), Filter::tag("x")]);
+        assert!(everything.is_empty());
+        assert_eq!(everything.to_string(), "");
+        assert!(everything.validate().is_ok());
+        let filter = Filter::tag("y").and(everything.clone());
+        assert_eq!(filter.to_string(), r#"_tags:"y""#);
+        assert_eq!(
+            filter.to_array(FilterArray::Tag).unwrap(),
+            serde_json::json!(["y"])
+        );
+    }
+
+    #[test]
+    fn test_negation() {
+        assert_eq!(
+            Filter::ne("platform", "PC").to_string(),
+            r#"NOT platform:"PC""#
+        );
+        // documents without the attribute match the negation, the inverted comparison drops them
+        assert_eq!((!Filter::lt("price", 10)).to_string(), "NOT price < 10");
+        assert_eq!(
+            (!Filter::eq("available", true)).to_string(),
+            "NOT available:true"
+        );
+        assert_eq!(!!Filter::tag("beta"), Filter::tag("beta"));
+        let filter = !(Filter::eq("genre", "rpg").or(Filter::range("year", 2000, 2010)));
+        assert_eq!(
+            filter.to_string(),
+            r#"NOT genre:"rpg" AND NOT year:2000 TO 2010"#
+        );
+        assert!(filter.validate().is_ok());
+        let filter = !(Filter::tag("beta").and(Filter::tag("alpha")));
+        assert_eq!(
+            filter.to_string(),
+            r#"NOT _tags:"beta" OR NOT _tags:"alpha""#
+        );
+        assert!(filter.validate().is_ok());
+        // the expansion would mix tag and numeric filters in an OR group
+        let filter = !(Filter::tag("beta").and(Filter::gt("price", 10)));
+        assert_eq!(filter.to_string(), r#"NOT (_tags:"beta" AND price > 10)"#);
+        assert!(filter.validate().is_err());
+        let filter = !Filter::raw("a:1 OR b:2");
+        assert_eq!(filter.to_string(), "NOT (a:1 OR b:2)");
+        assert!(filter.validate().is_err());
+        assert!((!Filter::raw("a:1")).validate().is_ok());
+    }
+
+    #[test]
+    fn test_scores() {
+        let filter = Filter::eq("brand", "Apple")
+            .score(3)
+            .or(Filter::tag("new").score(1));
+        assert_eq!(
+            filter.to_string(),
+            r#"brand:"Apple"<score=3> OR _tags:"new"<score=1>"#
+        );
+        assert!(filter.validate().is_ok());
+        assert!(Filter::gt("price", 10).score(2).validate().is_err());
+        assert!((!Filter::tag("new").score(2)).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_refuses_what_algolia_refuses() {
+        let nested_and = Filter::eq("a", "1")
+            .and(Filter::eq("b", "2"))
+            .or(Filter::eq("c", "3"));
+        assert!(nested_and.validate().is_err());
+        let mixed = Filter::eq("genre", "rpg").or(Filter::gt("price", 10));
+        assert!(mixed.validate().is_err());
+        assert!(Filter::tag("a").and(mixed).validate().is_err());
+        let negated_group = Filter::Not(Box::new(Filter::Or(vec![
+            Filter::tag("a"),
+            Filter::tag("b"),
+        ])));
+        assert!(negated_group.validate().is_err());
+        let valid = Filter::eq("genre", "rpg")
+            .or(Filter::tag("rpg"))
+            .and(Filter::lt("price", 10).or(Filter::range("year", 2000, 2010)))
+            .and(Filter::raw("a:1 AND b:2 OR c:3"));
+        assert!(valid.validate().is_ok());
+    }
+
+    #[test]
+    fn test_filter_arrays() {
+        let facets = Filter::eq("genre", "rpg")
+            .or(Filter::eq("genre", "-1"))
+            .and(Filter::ne("platform", "pc"))
+            .and(Filter::eq("multiplayer", true))
+            .and(Filter::tag("beta"));
+        assert_eq!(
+            facets.to_array(FilterArray::Facet).unwrap(),
+            serde_json::json!([
+                ["genre:rpg", "genre:\\-1"],
+                "platform:-pc",
+                "multiplayer:true",
+                "_tags:beta"
+            ])
+        );
+        let numerics = Filter::gt("price", 10)
+            .and(Filter::range("year", 2000, 2010).or(Filter::eq("year", 1999)));
+        assert_eq!(
+            numerics.to_array(FilterArray::Numeric).unwrap(),
+            serde_json::json!(["price > 10", ["year:2000 TO 2010", "year = 1999"]])
+        );
+        let tags = Filter::any([!Filter::tag("alpha"), Filter::tag("-1")]);
+        assert_eq!(
+            tags.to_array(FilterArray::Tag).unwrap(),
+            serde_json::json!([["-alpha", "\\-1"]])
+        );
+        assert_eq!(
+            Filter::raw("genre:rpg")
+                .to_array(FilterArray::Facet)
+                .unwrap(),
+            serde_json::json!(["genre:rpg"])
+        );
+        assert_eq!(
+            Filter::all(Vec::<Filter>::new())
+                .to_array(FilterArray::Tag)
+                .unwrap(),
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn test_filter_arrays_refuse_other_filters() {
+        assert!(Filter::gt("price", 10)
+            .to_array(FilterArray::Facet)
+            .is_err());
+        assert!(Filter::eq("genre", "rpg")
+            .to_array(FilterArray::Numeric)
+            .is_err());
+        assert!(Filter::eq("genre", "rpg")
+            .to_array(FilterArray::Tag)
+            .is_err());
+        assert!(Filter::gt("price", 10)
+            .to_array(FilterArray::Optional)
+            .is_err());
+        assert!(Filter::eq("genre", "rpg")
+            .score(2)
+            .to_array(FilterArray::Facet)
+            .is_err());
+        // an OR of ANDs can not be written as arrays
+        let nested = Filter::tag("a").and(Filter::tag("b")).or(Filter::tag("c"));
+        assert!(nested.to_array(FilterArray::Tag).is_err());
+        assert!(Filter::Or(Vec::new()).to_array(FilterArray::Tag).is_err());
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        assert_eq!(
+            FilterNumber::from(u64::MAX),
+            FilterNumber::Float(u64::MAX as f64)
+        );
+        assert_eq!(FilterNumber::from(42u64), FilterNumber::Integer(42));
+        assert_eq!(
+            Filter::gt("published_at", 1_700_000_000_000i64).to_string(),
+            "published_at > 1700000000000"
+        );
+    }
+}
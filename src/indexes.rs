@@ -0,0 +1,394 @@
+//! Index management
+//! list, clear, delete, copy and move whole indexes
+use crate::{
+    algoliaobject::AlgoliaObject,
+    batch::BatchOperation,
+    error::EasyAlgoliaError,
+    request::AlgoliaRequest,
+    response::{
+        BatchResponse,
+        ReplaceAllObjectsResponse,
+        WriteResponse,
+    },
+    Client,
+    Index,
+};
+use futures::{
+    Stream,
+    StreamExt,
+};
+use http::Method;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+/// details of an index as returned by
+/// [client.list_indexes_async](crate::Client::list_indexes_async)
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexInfo {
+    pub name: String,
+    /// number of documents in the index
+    #[serde(default)]
+    pub entries: u64,
+    /// size of the documents in bytes
+    #[serde(default)]
+    pub data_size: u64,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// whether the index has tasks waiting to be published
+    #[serde(default)]
+    pub pending_task: bool,
+    /// replicas of the index, only set on primary indexes having replicas
+    #[serde(default)]
+    pub replicas: Vec<String>,
+    /// primary index, only set on replicas
+    #[serde(default)]
+    pub primary: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ListIndexesResponse {
+    items: Vec<IndexInfo>,
+}
+
+/// parts of an index copied along with its documents
+/// copying with a scope copies only the listed parts and keeps the destination documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyScope {
+    Settings,
+    Synonyms,
+    Rules,
+}
+
+#[derive(serde::Serialize)]
+struct IndexOperation<'a> {
+    operation: &'static str,
+    destination: &'a str,
+    #[serde(skip_serializing_if = "<[CopyScope]>::is_empty")]
+    scope: &'a [CopyScope],
+}
+
+impl Client {
+    /// list every index of the application
+    pub async fn list_indexes_async(&self) -> Result<Vec<IndexInfo>, EasyAlgoliaError> {
+        let response: ListIndexesResponse = self.send_request(list_indexes_request()).await?;
+        Ok(response.items)
+    }
+
+    /// same as [list_indexes_async](crate::Client::list_indexes_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn list_indexes(&self) -> Result<Vec<IndexInfo>, EasyAlgoliaError> {
+        let response: ListIndexesResponse = self.send_request_blocking(list_indexes_request())?;
+        Ok(response.items)
+    }
+
+    /// delete every document of the index, settings, synonyms and rules are kept
+    pub async fn clear_index_async(
+        &self,
+        index: &Index,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(clear_index_request(index)).await
+    }
+
+    /// same as [clear_index_async](crate::Client::clear_index_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn clear_index(&self, index: &Index) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(clear_index_request(index))
+    }
+
+    /// delete the index with its documents, settings, synonyms and rules
+    pub async fn delete_index_async(
+        &self,
+        index: &Index,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(delete_index_request(index)).await
+    }
+
+    /// same as [delete_index_async](crate::Client::delete_index_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn delete_index(&self, index: &Index) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(delete_index_request(index))
+    }
+
+    /// copy `source` into `destination`, replacing the destination
+    /// an empty `scope` copies everything, otherwise only the listed parts are copied and the
+    /// destination documents are kept
+    /// the returned task belongs to the `source` index
+    /// # Examples
+    /// ```ignore
+    ///    let live: Index = "Games".into();
+    ///    let staging: Index = "Games_staging".into();
+    ///    // only bring the settings and synonyms of the live index to staging
+    ///    let response = client
+    ///        .copy_index_async(&live, &staging, &[CopyScope::Settings, CopyScope::Synonyms])
+    ///        .await?;
+    ///    client.wait_task_async(&live, response.task_id).await?;
+    /// ```
+    pub async fn copy_index_async(
+        &self,
+        source: &Index,
+        destination: &Index,
+        scope: &[CopyScope],
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(index_operation_request(source, "copy", destination, scope)?)
+            .await
+    }
+
+    /// same as [copy_index_async](crate::Client::copy_index_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn copy_index(
+        &self,
+        source: &Index,
+        destination: &Index,
+        scope: &[CopyScope],
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(index_operation_request(source, "copy", destination, scope)?)
+    }
+
+    /// rename `source` to `destination`, the destination is replaced and `source` no longer exists
+    /// moving is atomic, searches on the destination never see a partial index
+    /// the returned task belongs to the `source` index
+    pub async fn move_index_async(
+        &self,
+        source: &Index,
+        destination: &Index,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(index_operation_request(source, "move", destination, &[])?)
+            .await
+    }
+
+    /// same as [move_index_async](crate::Client::move_index_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn move_index(
+        &self,
+        source: &Index,
+        destination: &Index,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(index_operation_request(source, "move", destination, &[])?)
+    }
+
+    /// atomically replace every document of the index with `objects`
+    /// documents are written to a temporary index holding a copy of the index settings,
+    /// synonyms and rules, once every write is published the temporary index is moved over the
+    /// index so searches never see an empty or partial index
+    /// the temporary index is deleted if any step fails
+    /// # Examples
+    /// ```ignore
+    ///    let games = futures::stream::iter(load_games_from_database());
+    ///    client.replace_all_objects_async(&index, games).await?;
+    /// ```
+    pub async fn replace_all_objects_async<T>(
+        &self,
+        index: &Index,
+        objects: impl Stream<Item = T>,
+    ) -> Result<ReplaceAllObjectsResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let tmp_index = tmp_index(index);
+        let response = self.replace_through(index, &tmp_index, objects).await;
+        if response.is_err() {
+            // best effort cleanup, the original error is the one worth reporting
+            let _ = self.delete_index_async(&tmp_index).await;
+        }
+        response
+    }
+
+    /// same as [replace_all_objects_async](crate::Client::replace_all_objects_async) but blocking
+    /// in nature the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn replace_all_objects<T>(
+        &self,
+        index: &Index,
+        objects: impl IntoIterator<Item = T>,
+    ) -> Result<ReplaceAllObjectsResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let tmp_index = tmp_index(index);
+        let response = self.replace_through_blocking(index, &tmp_index, objects);
+        if response.is_err() {
+            // best effort cleanup, the original error is the one worth reporting
+            let _ = self.delete_index(&tmp_index);
+        }
+        response
+    }
+
+    async fn replace_through<T>(
+        &self,
+        index: &Index,
+        tmp_index: &Index,
+        objects: impl Stream<Item = T>,
+    ) -> Result<ReplaceAllObjectsResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let copy_operation = self
+            .copy_index_async(index, tmp_index, &REPLACE_SCOPE)
+            .await?;
+        self.wait_task_async(index, copy_operation.task_id).await?;
+
+        let mut batch = BatchResponse::default();
+        let mut chunks = std::pin::pin!(objects.chunks(self.batch_size));
+        while let Some(chunk) = chunks.next().await {
+            let operations = chunk.into_iter().map(BatchOperation::upsert).collect();
+            let response = self.batch_async(tmp_index, operations).await?;
+            batch.task_ids.extend(response.task_ids);
+            batch.object_ids.extend(response.object_ids);
+        }
+        for task_id in &batch.task_ids {
+            self.wait_task_async(tmp_index, *task_id).await?;
+        }
+
+        let move_operation = self.move_index_async(tmp_index, index).await?;
+        self.wait_task_async(tmp_index, move_operation.task_id)
+            .await?;
+        Ok(ReplaceAllObjectsResponse {
+            copy_operation,
+            batch,
+            move_operation,
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    fn replace_through_blocking<T>(
+        &self,
+        index: &Index,
+        tmp_index: &Index,
+        objects: impl IntoIterator<Item = T>,
+    ) -> Result<ReplaceAllObjectsResponse, EasyAlgoliaError>
+    where
+        T: AlgoliaObject,
+    {
+        let copy_operation = self.copy_index(index, tmp_index, &REPLACE_SCOPE)?;
+        self.wait_task(index, copy_operation.task_id)?;
+
+        let mut batch = BatchResponse::default();
+        let mut objects = objects.into_iter().peekable();
+        while objects.peek().is_some() {
+            let operations = objects
+                .by_ref()
+                .take(self.batch_size)
+                .map(BatchOperation::upsert)
+                .collect();
+            let response = self.batch(tmp_index, operations)?;
+            batch.task_ids.extend(response.task_ids);
+            batch.object_ids.extend(response.object_ids);
+        }
+        for task_id in &batch.task_ids {
+            self.wait_task(tmp_index, *task_id)?;
+        }
+
+        let move_operation = self.move_index(tmp_index, index)?;
+        self.wait_task(tmp_index, move_operation.task_id)?;
+        Ok(ReplaceAllObjectsResponse {
+            copy_operation,
+            batch,
+            move_operation,
+        })
+    }
+}
+
+/// parts of the index kept by [replace_all_objects_async](crate::Client::replace_all_objects_async)
+const REPLACE_SCOPE: [CopyScope; 3] = [CopyScope::Settings, CopyScope::Synonyms, CopyScope::Rules];
+
+/// temporary index documents are written to before replacing `index`
+fn tmp_index(index: &Index) -> Index {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    format!("{}_tmp_{}", index.index(), millis).into()
+}
+
+fn list_indexes_request() -> AlgoliaRequest {
+    AlgoliaRequest::read(Method::GET, "/1/indexes".into())
+}
+
+fn clear_index_request(index: &Index) -> AlgoliaRequest {
+    AlgoliaRequest::write(Method::POST, format!("/1/indexes/{}/clear", index.index()))
+}
+
+fn delete_index_request(index: &Index) -> AlgoliaRequest {
+    AlgoliaRequest::write(Method::DELETE, format!("/1/indexes/{}", index.index()))
+}
+
+fn index_operation_request(
+    source: &Index,
+    operation: &'static str,
+    destination: &Index,
+    scope: &[CopyScope],
+) -> Result<AlgoliaRequest, EasyAlgoliaError> {
+    let path = format!("/1/indexes/{}/operation", source.index());
+    let body = IndexOperation {
+        operation,
+        destination: destination.index(),
+        scope,
+    };
+    AlgoliaRequest::write(Method::POST, path).json(&body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_index_operation_serialize() {
+        let copy = IndexOperation {
+            operation: "copy",
+            destination: "Games_tmp",
+            scope: &[CopyScope::Settings, CopyScope::Rules],
+        };
+        assert_eq!(
+            serde_json::to_value(copy).unwrap(),
+            serde_json::json!({
+                "operation": "copy",
+                "destination": "Games_tmp",
+                "scope": ["settings", "rules"]
+            })
+        );
+        let rename = IndexOperation {
+            operation: "move",
+            destination: "Games",
+            scope: &[],
+        };
+        assert_eq!(
+            serde_json::to_value(rename).unwrap(),
+            serde_json::json!({ "operation": "move", "destination": "Games" })
+        );
+    }
+
+    #[test]
+    fn test_list_indexes_deserialize() {
+        let body = serde_json::json!({
+            "items": [{
+                "name": "Games",
+                "createdAt": "2024-03-10T10:00:00.000Z",
+                "updatedAt": "2024-03-11T10:00:00.000Z",
+                "entries": 120,
+                "dataSize": 4096,
+                "fileSize": 8192,
+                "lastBuildTimeS": 0,
+                "numberOfPendingTasks": 0,
+                "pendingTask": false,
+                "replicas": ["Games_by_price"]
+            }],
+            "nbPages": 1
+        });
+        let response: ListIndexesResponse = serde_json::from_value(body).unwrap();
+        let games = &response.items[0];
+        assert_eq!((games.entries, games.data_size), (120, 4096));
+        assert_eq!(games.replicas, vec!["Games_by_price"]);
+        assert!(games.primary.is_none());
+    }
+}
@@ -1,7 +1,6 @@
 //! easy Algolia is unofficial Rust client for algolia admin to update and insert data in Algolia
 //! Search Engine
-#[allow(non_snake_case)]
-#[allow(non_snake_case)]
+#![allow(non_snake_case)]
 pub mod client_builder;
 pub mod error;
 use error::EasyAlgoliaError;
@@ -10,10 +9,64 @@ use secrecy::{
     Secret,
 };
 pub mod algoliaobject;
-use crate::algoliaobject::AlgoliaObject;
-use reqwest::Client as Rq;
+pub mod batch;
+pub mod browse;
+pub mod delete;
+pub mod filter;
+pub mod indexes;
+pub mod objects;
+pub mod partial_update;
+mod request;
+pub mod response;
+pub mod retry;
+pub mod task;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+use crate::{
+    algoliaobject::{
+        AlgoliaObject,
+        SearchQuery,
+    },
+    error::ErrorKind,
+    request::AlgoliaRequest,
+    response::{
+        SearchResponse,
+        WriteResponse,
+    },
+    retry::{
+        CallKind,
+        DefaultRetryStrategy,
+        HostStatus,
+        RetryStrategy,
+    },
+    transport::{
+        HttpErrorKind,
+        HttpRequest,
+        HttpRequester,
+        HttpResponse,
+    },
+};
+use http::{
+    header::{
+        HeaderMap,
+        HeaderValue,
+        CONTENT_TYPE,
+        USER_AGENT,
+    },
+    Method,
+    StatusCode,
+};
+use percent_encoding::{
+    utf8_percent_encode,
+    AsciiSet,
+    NON_ALPHANUMERIC,
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
 
 /// index object to store the index of the Algoia
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Index {
     index: String,
 }
@@ -97,7 +150,6 @@ pub struct AlgoliaIndexSetting {
 ///  let client = Clientbuilder::build_from_env().unwarp();
 ///  client.delete_document_async(index,obj_id).await.unwarp();
 ///  ```
-
 #[derive(serde::Serialize)]
 pub struct ObjectId {
     obj_id: String,
@@ -122,21 +174,275 @@ impl AlgoliaObject for ObjectId {
     }
 }
 
+/// characters kept as is when an object id is put in an url path
+const OBJECT_ID_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// api path of a document, object ids are percent encoded since they can hold any character
+pub(crate) fn object_path(index: &Index, object_id: &str) -> String {
+    format!(
+        "/1/indexes/{}/{}",
+        index.index(),
+        utf8_percent_encode(object_id, OBJECT_ID_ENCODE_SET)
+    )
+}
+
+/// deserialize a response body, non success status codes are returned as an error holding the
+/// status and the message sent by Algolia
+fn parse_response<R>(status: StatusCode, body: &str) -> Result<R, EasyAlgoliaError>
+where
+    R: DeserializeOwned,
+{
+    if !status.is_success() {
+        return Err(EasyAlgoliaError::from_response(status.as_u16(), body));
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
 /// Client to interact with algolia
 pub struct Client {
     api_key: Secret<String>,
     application_id: Secret<String>,
-    client: Rq,
+    requester: Arc<dyn HttpRequester>,
+    /// agent and custom headers sent with every request
+    default_headers: HeaderMap,
+    retry_strategy: Arc<dyn RetryStrategy>,
+    timeouts: client_builder::Timeouts,
+    batch_size: usize,
+    task_wait_policy: task::TaskWaitPolicy,
+}
+
+/// request writing a document, documents without object id get one generated by Algolia
+fn put_document_request<T>(index: &Index, document: &T) -> Result<AlgoliaRequest, EasyAlgoliaError>
+where
+    T: serde::Serialize + AlgoliaObject,
+{
+    let request = match document.get_object_id().as_str() {
+        // if object id is not present in algolia doc then put random object id
+        // random id is generted by algolia
+        "" => AlgoliaRequest::write(Method::POST, format!("/1/indexes/{}", index.index())),
+        object_id => AlgoliaRequest::write(Method::PUT, object_path(index, object_id)),
+    };
+    request.json(document)
+}
+
+fn delete_document_request<T>(
+    index: &Index,
+    document: T,
+) -> Result<AlgoliaRequest, EasyAlgoliaError>
+where
+    T: AlgoliaObject,
+{
+    match document.get_object_id().as_str() {
+        "" => Err(EasyAlgoliaError::new(
+            ErrorKind::InvalidRequest,
+            Some("object id must be present for document delete method".into()),
+        )),
+        object_id => Ok(AlgoliaRequest::write(
+            Method::DELETE,
+            object_path(index, object_id),
+        )),
+    }
+}
+
+fn search_request(index: &Index, query: &SearchQuery) -> Result<AlgoliaRequest, EasyAlgoliaError> {
+    let path = format!("/1/indexes/{}/query", index.index());
+    let body = serde_json::json!({ "params": query.to_params_string()? });
+    AlgoliaRequest::read(Method::POST, path).json(&body)
+}
+
+/// what to do once a request was sent to a host
+enum Attempt {
+    /// the response or error to give back to the caller
+    Done(Result<HttpResponse, EasyAlgoliaError>),
+    /// the host failed, the request can be sent to the next one
+    Retry(EasyAlgoliaError),
+}
+
+/// error once every host of the retry strategy was tried
+fn hosts_exhausted(last_error: Option<EasyAlgoliaError>) -> EasyAlgoliaError {
+    last_error.unwrap_or_else(|| {
+        EasyAlgoliaError::new(
+            ErrorKind::Transport,
+            Some("the retry strategy gave no host to send the request to".into()),
+        )
+    })
+}
+
+/// deserialize a response where a missing resource gives `None`
+fn parse_optional_response<R>(response: HttpResponse) -> Result<Option<R>, EasyAlgoliaError>
+where
+    R: DeserializeOwned,
+{
+    if response.status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    parse_response(response.status, &response.body).map(Some)
 }
 
 impl Client {
-    pub(crate) fn new(api_key: &str, application_id: &str) -> Self {
+    pub(crate) fn new(
+        api_key: &str,
+        application_id: &str,
+        requester: Arc<dyn HttpRequester>,
+    ) -> Self {
+        let agent = HeaderValue::from_static(client_builder::AGENT);
+        let mut default_headers = HeaderMap::new();
+        default_headers.insert(USER_AGENT, agent.clone());
+        default_headers.insert("X-Algolia-Agent", agent);
         Self {
             api_key: Secret::new(String::from(api_key)),
             application_id: Secret::new(String::from(application_id)),
-            client: Rq::new(),
+            requester,
+            default_headers,
+            retry_strategy: Arc::new(DefaultRetryStrategy::new(application_id)),
+            timeouts: client_builder::Timeouts::default(),
+            batch_size: batch::DEFAULT_BATCH_SIZE,
+            task_wait_policy: task::TaskWaitPolicy::default(),
+        }
+    }
+
+    /// send the request and deserialize the response body into `R`
+    async fn send_request<R>(&self, request: AlgoliaRequest) -> Result<R, EasyAlgoliaError>
+    where
+        R: DeserializeOwned,
+    {
+        let response = self.execute(&request).await?;
+        parse_response(response.status, &response.body)
+    }
+
+    /// same as [send_request](crate::Client::send_request) but a missing resource gives `None`
+    async fn send_optional_request<R>(
+        &self,
+        request: AlgoliaRequest,
+    ) -> Result<Option<R>, EasyAlgoliaError>
+    where
+        R: DeserializeOwned,
+    {
+        parse_optional_response(self.execute(&request).await?)
+    }
+
+    /// send the request to the hosts of the retry strategy until one of them answers, see
+    /// [RetryStrategy](crate::retry::RetryStrategy) for when a request is retried
+    async fn execute(&self, request: &AlgoliaRequest) -> Result<HttpResponse, EasyAlgoliaError> {
+        let mut last_error = None;
+        for host in self.retry_strategy.hosts(request.call) {
+            let result = self.requester.send(self.http_request(&host, request)).await;
+            match self.attempt(&host, request, result) {
+                Attempt::Done(response) => return response,
+                Attempt::Retry(error) => last_error = Some(error),
+            }
         }
+        Err(hosts_exhausted(last_error))
     }
+
+    /// same as [send_request](crate::Client::send_request) but blocking
+    #[cfg(feature = "blocking")]
+    fn send_request_blocking<R>(&self, request: AlgoliaRequest) -> Result<R, EasyAlgoliaError>
+    where
+        R: DeserializeOwned,
+    {
+        let response = self.execute_blocking(&request)?;
+        parse_response(response.status, &response.body)
+    }
+
+    /// same as [send_optional_request](crate::Client::send_optional_request) but blocking
+    #[cfg(feature = "blocking")]
+    fn send_optional_request_blocking<R>(
+        &self,
+        request: AlgoliaRequest,
+    ) -> Result<Option<R>, EasyAlgoliaError>
+    where
+        R: DeserializeOwned,
+    {
+        parse_optional_response(self.execute_blocking(&request)?)
+    }
+
+    /// same as [execute](crate::Client::execute) but blocking
+    #[cfg(feature = "blocking")]
+    fn execute_blocking(&self, request: &AlgoliaRequest) -> Result<HttpResponse, EasyAlgoliaError> {
+        let mut last_error = None;
+        for host in self.retry_strategy.hosts(request.call) {
+            let result = self
+                .requester
+                .send_blocking(self.http_request(&host, request));
+            match self.attempt(&host, request, result) {
+                Attempt::Done(response) => return response,
+                Attempt::Retry(error) => last_error = Some(error),
+            }
+        }
+        Err(hosts_exhausted(last_error))
+    }
+
+    /// the request sent to `host`, with the Algolia credentials headers
+    fn http_request(&self, host: &str, request: &AlgoliaRequest) -> HttpRequest {
+        let mut headers = self.default_headers.clone();
+        for (name, value) in [
+            ("X-Algolia-API-Key", self.api_key.expose_secret()),
+            (
+                "X-Algolia-Application-Id",
+                self.application_id.expose_secret(),
+            ),
+        ] {
+            // credentials that are not valid header values are left out, Algolia rejects the
+            // request as unauthorized
+            if let Ok(mut value) = HeaderValue::from_str(value) {
+                value.set_sensitive(true);
+                headers.insert(name, value);
+            }
+        }
+        if request.body.is_some() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        HttpRequest {
+            method: request.method.clone(),
+            url: format!("{host}{}", request.path),
+            headers,
+            body: request.body.clone(),
+            timeout: match request.call {
+                CallKind::Read => self.timeouts.read,
+                CallKind::Write => self.timeouts.write,
+            },
+        }
+    }
+
+    /// report the health of `host` and decide whether the request goes to the next host
+    fn attempt(
+        &self,
+        host: &str,
+        request: &AlgoliaRequest,
+        result: Result<HttpResponse, transport::HttpError>,
+    ) -> Attempt {
+        let error = match result {
+            Ok(response) if !response.status.is_server_error() => {
+                self.retry_strategy.report(host, HostStatus::Up);
+                return Attempt::Done(Ok(response));
+            }
+            Ok(response) => {
+                EasyAlgoliaError::from_response(response.status.as_u16(), &response.body)
+            }
+            // the request could not be built, no other host would take it
+            Err(err) if err.kind() == HttpErrorKind::InvalidRequest => {
+                return Attempt::Done(Err(err.into()))
+            }
+            // the connection could not be established, Algolia never saw the request
+            Err(err) if err.kind() == HttpErrorKind::Connect => {
+                self.retry_strategy.report(host, HostStatus::Down);
+                return Attempt::Retry(err.into());
+            }
+            Err(err) => err.into(),
+        };
+        self.retry_strategy.report(host, HostStatus::Down);
+        if request.is_idempotent() {
+            Attempt::Retry(error)
+        } else {
+            Attempt::Done(Err(error))
+        }
+    }
+
     /// update or insert a data into given algolia index
     /// if the contained document objectId is present in algolia index, then this function will
     /// update document with new values if the document does default impls the implmentation
@@ -163,122 +469,33 @@ impl Client {
     ///   } ;
     ///  client.put_document_async("someIndex".into(),doc)
     /// ```
-
-    pub async fn put_document_async<T: AlgoliaObject>(
+    /// the returned [WriteResponse](crate::response::WriteResponse) holds the task id of the
+    /// write, pass it to [wait_task_async](crate::Client::wait_task_async) to wait until the
+    /// document is searchable
+    pub async fn put_document_async<T>(
         &self,
         index: &Index,
         document: &T,
-    ) -> Result<(), EasyAlgoliaError>
+    ) -> Result<WriteResponse, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let mut is_object_is_present = false;
-        let path = match document.get_object_id().as_str() {
-            // if object id is not present in algolia doc then put random object id
-            // random id is generted by algolia
-            "" => {
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                )
-            }
-            _ => {
-                is_object_is_present = true;
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                    document.get_object_id()
-                )
-            }
-        };
-        let mut client = match is_object_is_present {
-            true => self.client.put(path),
-            false => self.client.post(path),
-        };
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        client = client.json(&document);
-
-        match client.send().await {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(k.text().await.unwrap()),
-                    ));
-                }
-
-                Ok(())
-            }
-            Err(err) => Err(err.into()),
-        }
+        self.send_request(put_document_request(index, document)?)
+            .await
     }
 
     /// same as [put_document_async](crate::Client::put_document_async) but blocking in nature
-    /// /// under the hood it still uses asyn reqwest method only , but the runtime is block by
-    /// `futures::executor::block_on`
-    pub fn put_document<T: AlgoliaObject>(
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn put_document<T>(
         &self,
         index: &Index,
         document: &T,
-    ) -> Result<(), EasyAlgoliaError>
+    ) -> Result<WriteResponse, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let mut is_object_is_present = false;
-        let path = match document.get_object_id().as_str() {
-            // if object id is not present in algolia doc then put random object id
-            // random id is generted by algolia
-            "" => {
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                )
-            }
-            _ => {
-                is_object_is_present = true;
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                    document.get_object_id()
-                )
-            }
-        };
-        let mut client = match is_object_is_present {
-            true => self.client.put(path),
-            false => self.client.post(path),
-        };
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        client = client.json(&document);
-
-        match futures::executor::block_on(client.send()) {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(futures::executor::block_on(k.text()).unwrap()),
-                    ));
-                }
-
-                Ok(())
-            }
-            Err(err) => Err(err.into()),
-        }
+        self.send_request_blocking(put_document_request(index, document)?)
     }
 
     /// update or insert a data into given algolia index
@@ -311,95 +528,26 @@ impl Client {
         &self,
         index: &Index,
         document: T,
-    ) -> Result<(), EasyAlgoliaError>
+    ) -> Result<WriteResponse, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let path = match document.get_object_id().as_str() {
-            "" => {
-                return Err(EasyAlgoliaError::new(
-                    error::ErrorKind::RequestError,
-                    Some("object id must be present for document delete method".into()),
-                ));
-            }
-            _ => {
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                    document.get_object_id()
-                )
-            }
-        };
-
-        let mut client = self.client.delete(path);
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        match client.send().await {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(k.text().await.unwrap()),
-                    ));
-                }
-
-                Ok(())
-            }
-            Err(err) => Err(err.into()),
-        }
+        self.send_request(delete_document_request(index, document)?)
+            .await
     }
 
     /// same as [delete_document_async](crate::Client::delete_document_async) but its synchronous in
-    /// nature under the hood it still uses asyn reqwest method only , but the runtime is block
-    /// by `futures::executor::block_on`
-    pub fn delete_document<T>(&self, index: &Index, document: T) -> Result<(), EasyAlgoliaError>
+    /// nature the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn delete_document<T>(
+        &self,
+        index: &Index,
+        document: T,
+    ) -> Result<WriteResponse, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let path = match document.get_object_id().as_str() {
-            "" => {
-                return Err(EasyAlgoliaError::new(
-                    error::ErrorKind::RequestError,
-                    Some("object id must be present for document delete method".into()),
-                ));
-            }
-            _ => {
-                format!(
-                    "https://{}.algolia.net/1/indexes/{}/{}",
-                    &self.application_id.expose_secret(),
-                    index.index(),
-                    document.get_object_id()
-                )
-            }
-        };
-
-        let mut client = self.client.delete(path);
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        match futures::executor::block_on(client.send()) {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(futures::executor::block_on(k.text()).unwrap()),
-                    ));
-                }
-
-                Ok(())
-            }
-            Err(err) => Err(err.into()),
-        }
+        self.send_request_blocking(delete_document_request(index, document)?)
     }
 
     /// get settings for a given index
@@ -408,44 +556,16 @@ impl Client {
     ///    let index:Index = "SomeIndex".into();
     ///    let setting = client.get_index_setting().await?;
     /// ```
-    pub async fn get_index_setting<T: AlgoliaObject>(
+    pub async fn get_index_setting<T>(
         &self,
         index: &Index,
     ) -> Result<AlgoliaIndexSetting, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let path = format!(
-            "https://{}.algolia.net/1/indexes/{}/settings",
-            &self.application_id.expose_secret(),
-            index.index()
-        );
-        let mut client = self.client.get(path);
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        match client.send().await {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(k.text().await.unwrap()),
-                    ));
-                } else {
-                    let setting: AlgoliaIndexSetting = k
-                        .json::<AlgoliaIndexSetting>()
-                        .await
-                        .map_err(|_| EasyAlgoliaError::new(error::ErrorKind::RequestError, None))?;
-
-                    Ok(setting)
-                }
-            }
-            Err(err) => Err(err.into()),
-        }
+        let path = format!("/1/indexes/{}/settings", index.index());
+        self.send_request(AlgoliaRequest::read(Method::GET, path))
+            .await
     }
 
     /// upload settings for a given index
@@ -455,40 +575,309 @@ impl Client {
     ///    let index_setting = AlgoliaIndexSetting::default();
     ///    let setting = client.update_index_setting().await?;
     /// ```
-    pub async fn update_index_setting<T: AlgoliaObject>(
+    pub async fn update_index_setting<T>(
         &self,
         index: &Index,
         setting: AlgoliaIndexSetting,
-    ) -> Result<(), EasyAlgoliaError>
+    ) -> Result<WriteResponse, EasyAlgoliaError>
     where
         T: serde::Serialize + AlgoliaObject,
     {
-        let path = format!(
-            "https://{}.algolia.net/1/indexes/{}/settings",
-            &self.application_id.expose_secret(),
-            index.index()
-        );
-        let mut client = self.client.put(path);
-        client = client.header("X-Algolia-API-Key", self.api_key.expose_secret());
-        client = client.header(
-            "X-Algolia-Application-Id",
-            self.application_id.expose_secret(),
-        );
-        client = client.json(&setting);
-        match client.send().await {
-            Ok(k) => {
-                if k.status() > reqwest::StatusCode::from_u16(200).unwrap()
-                    || k.status() < reqwest::StatusCode::from_u16(200).unwrap()
-                {
-                    return Err(EasyAlgoliaError::new(
-                        error::ErrorKind::RequestError,
-                        Some(k.text().await.unwrap()),
-                    ));
-                } else {
-                    Ok(())
+        let path = format!("/1/indexes/{}/settings", index.index());
+        self.send_request(AlgoliaRequest::write(Method::PUT, path).json(&setting)?)
+            .await
+    }
+
+    /// search documents of the given index
+    /// hits are deserialized into `T`, use `serde_json::Value` if you want raw json
+    /// ```ignore
+    ///    let index: Index = "Games".into();
+    ///    let query = SearchQuery::new()
+    ///        .query("last of us")
+    ///        .filter("platform:playstation");
+    ///    let response: SearchResponse<Game> = client.search_async(&index, query).await?;
+    ///    println!("found {} games", response.nb_hits);
+    /// ```
+    pub async fn search_async<T>(
+        &self,
+        index: &Index,
+        query: SearchQuery,
+    ) -> Result<SearchResponse<T>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        self.send_request(search_request(index, &query)?).await
+    }
+
+    /// same as [search_async](crate::Client::search_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn search<T>(
+        &self,
+        index: &Index,
+        query: SearchQuery,
+    ) -> Result<SearchResponse<T>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        self.send_request_blocking(search_request(index, &query)?)
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+    use std::{
+        io::{
+            BufRead,
+            BufReader,
+            Read,
+            Write,
+        },
+        net::TcpListener,
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    };
+
+    /// serve the same raw http response to every connection, returns the url of the server and
+    /// the number of requests it received
+    fn stub_host(response: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&received);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
                 }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut stream = reader.into_inner();
+                stream.write_all(response.as_bytes()).unwrap();
             }
-            Err(err) => Err(err.into()),
-        }
+        });
+        (format!("http://{address}"), received)
+    }
+
+    /// client sending every request to the given `http://127.0.0.1:port` hosts in order
+    fn client_with_hosts(urls: &[&str]) -> Client {
+        let hosts = urls
+            .iter()
+            .map(|url| {
+                let port = url.rsplit(':').next().unwrap().parse().unwrap();
+                retry::Host::new("127.0.0.1")
+                    .scheme(retry::Scheme::Http)
+                    .port(port)
+            })
+            .collect();
+        let requester = transport::ReqwestRequester::new(Default::default()).unwrap();
+        let mut client = Client::new("key", "app", Arc::new(requester));
+        client.retry_strategy = Arc::new(DefaultRetryStrategy::with_hosts(hosts));
+        client
+    }
+
+    /// client pointing to a single host serving `response`
+    fn stub_client(response: &'static str) -> Client {
+        client_with_hosts(&[&stub_host(response).0])
+    }
+
+    /// url of a host accepting connections but never answering
+    fn hanging_host() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let connections: Vec<_> = listener.incoming().collect();
+            drop(connections);
+        });
+        format!("http://{address}")
+    }
+
+    /// url of a closed port, connecting to it fails
+    fn dead_host() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn response(status: &str, body: &str) -> &'static str {
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        Box::leak(response.into_boxed_str())
+    }
+
+    #[test]
+    fn test_object_path_is_encoded() {
+        let index: Index = "Games".into();
+        assert_eq!(
+            object_path(&index, "halo/2 edition"),
+            "/1/indexes/Games/halo%2F2%20edition"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_body_is_a_decode_error() {
+        let client = stub_client(response("200 OK", ""));
+        let err = client
+            .search_async::<serde_json::Value>(&"Games".into(), SearchQuery::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Decode);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_is_a_decode_error() {
+        let client = stub_client(response("200 OK", r#"{"hits": [{"name": "#));
+        let err = client
+            .get_index_setting::<ObjectId>(&"Games".into())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Decode);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_body_is_an_error() {
+        let client = stub_client(
+            "HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\n{\"taskID\"",
+        );
+        let result = client.clear_index_async(&"Games".into()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_error_status_with_empty_body() {
+        let client = stub_client(response("503 Service Unavailable", ""));
+        let err = client
+            .delete_index_async(&"Games".into())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ServerError);
+        assert_eq!(err.status(), Some(503));
+        assert_eq!(err.message(), None);
+    }
+
+    #[tokio::test]
+    async fn test_error_status_with_malformed_body() {
+        let client = stub_client(response("400 Bad Request", "{ not json"));
+        let err = client
+            .delete_by_async(&"Games".into(), SearchQuery::new().filter("tenant:42"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidRequest);
+        assert_eq!(err.message(), Some("{ not json"));
+    }
+
+    #[tokio::test]
+    async fn test_created_status_is_a_success() {
+        let client = stub_client(response(
+            "201 Created",
+            r#"{"createdAt":"2024-03-10T10:00:00.000Z","taskID":7,"objectID":"abc"}"#,
+        ));
+        let document = serde_json::json!({ "name": "Halo" });
+        let write = client
+            .put_document_async(&"Games".into(), &document)
+            .await
+            .unwrap();
+        assert_eq!(write.task_id.get(), 7);
+        assert_eq!(write.object_id.as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_object_is_none() {
+        let client = stub_client(response("404 Not Found", ""));
+        let game: Option<serde_json::Value> = client
+            .get_object_async(&"Games".into(), &"halo".into(), None)
+            .await
+            .unwrap();
+        assert!(game.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_fails_over_to_next_host() {
+        let (failing, failing_received) = stub_host(response("502 Bad Gateway", ""));
+        let (healthy, _) = stub_host(response("200 OK", r#"{"items":[]}"#));
+        let client = client_with_hosts(&[&dead_host(), &failing, &healthy]);
+        assert!(client.list_indexes_async().await.unwrap().is_empty());
+        assert_eq!(failing_received.load(Ordering::SeqCst), 1);
+        // both failing hosts are now skipped
+        assert_eq!(client.retry_strategy.hosts(CallKind::Read), vec![healthy]);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let (forbidden, _) = stub_host(response("403 Forbidden", r#"{"message":"no"}"#));
+        let (healthy, healthy_received) = stub_host(response("200 OK", r#"{"items":[]}"#));
+        let client = client_with_hosts(&[&forbidden, &healthy]);
+        let err = client.list_indexes_async().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unauthorized);
+        assert_eq!(healthy_received.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_write_is_retried_only_before_sending() {
+        let (failing, _) = stub_host(response("500 Internal Server Error", ""));
+        let (healthy, healthy_received) =
+            stub_host(response("200 OK", r#"{"taskID":1,"objectIDs":[]}"#));
+        let client = client_with_hosts(&[&failing, &healthy]);
+        let err = client.clear_index_async(&"Games".into()).await.unwrap_err();
+        assert_eq!(err.status(), Some(500));
+        assert_eq!(healthy_received.load(Ordering::SeqCst), 0);
+
+        let client = client_with_hosts(&[&dead_host(), &healthy]);
+        let operations: Vec<batch::BatchOperation<ObjectId>> =
+            vec![batch::BatchOperation::DeleteObject("halo".into())];
+        client
+            .batch_async(&"Games".into(), operations)
+            .await
+            .unwrap();
+        assert_eq!(healthy_received.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout_fails_over_to_next_host() {
+        let (healthy, _) = stub_host(response("200 OK", r#"{"items":[]}"#));
+        let mut client = client_with_hosts(&[&hanging_host(), &healthy]);
+        client.timeouts.read = std::time::Duration::from_millis(200);
+        assert!(client.list_indexes_async().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_calls_need_no_runtime() {
+        let client = stub_client(response(
+            "200 OK",
+            r#"{"hits":[{"name":"Halo"},{"name":"Doom"}],"nbHits":2,"page":0,"nbPages":1,
+                "hitsPerPage":20,"processingTimeMS":1}"#,
+        ));
+        let games: Vec<serde_json::Value> = client
+            .browse(&"Games".into(), browse::BrowseParams::new())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(games.len(), 2);
+        let found: SearchResponse<serde_json::Value> =
+            client.search(&"Games".into(), SearchQuery::new()).unwrap();
+        assert_eq!(found.hits[1]["name"], "Doom");
+
+        let client = stub_client(response("404 Not Found", ""));
+        let game: Option<serde_json::Value> = client
+            .get_object(&"Games".into(), &"halo".into(), None)
+            .unwrap();
+        assert!(game.is_none());
     }
 }
@@ -0,0 +1,166 @@
+//! Read documents back by object id
+//! see [client.get_object_async](crate::Client::get_object_async) and
+//! [client.get_objects_async](crate::Client::get_objects_async)
+use crate::{
+    error::EasyAlgoliaError,
+    object_path,
+    request::AlgoliaRequest,
+    Client,
+    Index,
+    ObjectId,
+};
+use http::Method;
+use serde::de::DeserializeOwned;
+
+#[derive(serde::Serialize)]
+struct GetObjectRequest {
+    #[serde(rename = "indexName")]
+    index_name: String,
+    #[serde(rename = "objectID")]
+    object_id: String,
+}
+
+#[derive(serde::Serialize)]
+struct GetObjectsRequest {
+    requests: Vec<GetObjectRequest>,
+}
+
+#[derive(serde::Deserialize)]
+struct GetObjectsResponse<T> {
+    results: Vec<Option<T>>,
+}
+
+fn get_object_request(
+    index: &Index,
+    object_id: &ObjectId,
+    attributes_to_retrieve: Option<&[&str]>,
+) -> AlgoliaRequest {
+    let request = AlgoliaRequest::read(Method::GET, object_path(index, &object_id.obj_id));
+    match attributes_to_retrieve {
+        Some(attributes) => request.query("attributesToRetrieve", &attributes.join(",")),
+        None => request,
+    }
+}
+
+/// request fetching the documents, `None` when there is nothing to fetch
+fn get_objects_request(
+    requests: impl IntoIterator<Item = (Index, ObjectId)>,
+) -> Result<Option<AlgoliaRequest>, EasyAlgoliaError> {
+    let requests = GetObjectsRequest {
+        requests: requests
+            .into_iter()
+            .map(|(index, object_id)| GetObjectRequest {
+                index_name: index.index,
+                object_id: object_id.obj_id,
+            })
+            .collect(),
+    };
+    if requests.requests.is_empty() {
+        return Ok(None);
+    }
+    AlgoliaRequest::read(Method::POST, "/1/indexes/*/objects".into())
+        .json(&requests)
+        .map(Some)
+}
+
+impl Client {
+    /// get a document by its object id, `None` is returned if the document does not exist
+    /// `attributes_to_retrieve` limits the attributes sent back by Algolia, all retrievable
+    /// attributes are sent when `None`
+    /// # Examples
+    /// ```ignore
+    ///    let index: Index = "Games".into();
+    ///    let game: Option<Game> = client.get_object_async(&index, &"last-of-us".into(), None).await?;
+    ///    let names: Option<serde_json::Value> = client
+    ///        .get_object_async(&index, &"last-of-us".into(), Some(&["name"]))
+    ///        .await?;
+    /// ```
+    pub async fn get_object_async<T>(
+        &self,
+        index: &Index,
+        object_id: &ObjectId,
+        attributes_to_retrieve: Option<&[&str]>,
+    ) -> Result<Option<T>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        let request = get_object_request(index, object_id, attributes_to_retrieve);
+        self.send_optional_request(request).await
+    }
+
+    /// same as [get_object_async](crate::Client::get_object_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn get_object<T>(
+        &self,
+        index: &Index,
+        object_id: &ObjectId,
+        attributes_to_retrieve: Option<&[&str]>,
+    ) -> Result<Option<T>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        let request = get_object_request(index, object_id, attributes_to_retrieve);
+        self.send_optional_request_blocking(request)
+    }
+
+    /// get many documents, possibly from different indexes, in a single request
+    /// results are in the same order as the requested pairs, missing documents are `None`
+    /// # Examples
+    /// ```ignore
+    ///    let games: Index = "Games".into();
+    ///    let results: Vec<Option<Game>> = client
+    ///        .get_objects_async([
+    ///            (games.clone(), "last-of-us".into()),
+    ///            (games, "god-of-war".into()),
+    ///        ])
+    ///        .await?;
+    /// ```
+    pub async fn get_objects_async<T>(
+        &self,
+        requests: impl IntoIterator<Item = (Index, ObjectId)>,
+    ) -> Result<Vec<Option<T>>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        let Some(request) = get_objects_request(requests)? else {
+            return Ok(Vec::new());
+        };
+        let response: GetObjectsResponse<T> = self.send_request(request).await?;
+        Ok(response.results)
+    }
+
+    /// same as [get_objects_async](crate::Client::get_objects_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn get_objects<T>(
+        &self,
+        requests: impl IntoIterator<Item = (Index, ObjectId)>,
+    ) -> Result<Vec<Option<T>>, EasyAlgoliaError>
+    where
+        T: DeserializeOwned,
+    {
+        let Some(request) = get_objects_request(requests)? else {
+            return Ok(Vec::new());
+        };
+        let response: GetObjectsResponse<T> = self.send_request_blocking(request)?;
+        Ok(response.results)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_objects_response_keeps_missing_entries() {
+        let body = r#"{ "results": [{ "name": "Halo" }, null, { "name": "Doom" }] }"#;
+        let response: GetObjectsResponse<serde_json::Value> = serde_json::from_str(body).unwrap();
+        let names: Vec<Option<&str>> = response
+            .results
+            .iter()
+            .map(|game| game.as_ref().map(|game| game["name"].as_str().unwrap()))
+            .collect();
+        assert_eq!(names, vec![Some("Halo"), None, Some("Doom")]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Partial document updates
+//! update some attributes of a document without sending the whole document, see
+//! [client.partial_update_async](crate::Client::partial_update_async)
+use crate::{
+    error::EasyAlgoliaError,
+    object_path,
+    request::AlgoliaRequest,
+    response::WriteResponse,
+    Client,
+    Index,
+    ObjectId,
+};
+use http::Method;
+use serde_json::{
+    Map,
+    Value,
+};
+
+/// Algolia built-in operations applied on an attribute by a partial update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltInOperation {
+    /// add a number to a numeric attribute
+    Increment,
+    /// subtract a number from a numeric attribute
+    Decrement,
+    /// append a value to an array attribute
+    Add,
+    /// append a value to an array attribute if it is not already present
+    AddUnique,
+    /// remove all occurrences of a value from an array attribute
+    Remove,
+    /// increment only if the current value is the given one, used for versioning
+    IncrementFrom,
+    /// set the value only if it is greater than the current one
+    IncrementSet,
+}
+
+impl BuiltInOperation {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Increment => "Increment",
+            Self::Decrement => "Decrement",
+            Self::Add => "Add",
+            Self::AddUnique => "AddUnique",
+            Self::Remove => "Remove",
+            Self::IncrementFrom => "IncrementFrom",
+            Self::IncrementSet => "IncrementSet",
+        }
+    }
+}
+
+/// attributes to update on a document
+/// an attribute holds a single change, calling the builder again on the same attribute replaces it
+/// # Usage
+/// ```
+/// use EasyAlgolia::partial_update::PartialUpdate;
+/// let update = PartialUpdate::new()
+///     .set("title", "The Last of Us Part II")
+///     .increment("views", 1)
+///     .add_unique("tags", "survival")
+///     .create_if_not_exists(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PartialUpdate {
+    attributes: Map<String, Value>,
+    create_if_not_exists: bool,
+}
+
+impl Default for PartialUpdate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialUpdate {
+    /// empty update, missing documents are created like Algolia does by default
+    pub fn new() -> Self {
+        Self {
+            attributes: Map::new(),
+            create_if_not_exists: true,
+        }
+    }
+
+    /// replace the value of the attribute
+    pub fn set(mut self, attribute: &str, value: impl Into<Value>) -> Self {
+        self.attributes.insert(attribute.into(), value.into());
+        self
+    }
+
+    /// apply a built-in operation on the attribute
+    pub fn operation(
+        mut self,
+        attribute: &str,
+        operation: BuiltInOperation,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.attributes.insert(
+            attribute.into(),
+            serde_json::json!({ "_operation": operation.name(), "value": value.into() }),
+        );
+        self
+    }
+
+    /// shorthand for [BuiltInOperation::Increment]
+    pub fn increment(self, attribute: &str, value: i64) -> Self {
+        self.operation(attribute, BuiltInOperation::Increment, value)
+    }
+
+    /// shorthand for [BuiltInOperation::Decrement]
+    pub fn decrement(self, attribute: &str, value: i64) -> Self {
+        self.operation(attribute, BuiltInOperation::Decrement, value)
+    }
+
+    /// shorthand for [BuiltInOperation::Add]
+    pub fn add(self, attribute: &str, value: impl Into<Value>) -> Self {
+        self.operation(attribute, BuiltInOperation::Add, value)
+    }
+
+    /// shorthand for [BuiltInOperation::AddUnique]
+    pub fn add_unique(self, attribute: &str, value: impl Into<Value>) -> Self {
+        self.operation(attribute, BuiltInOperation::AddUnique, value)
+    }
+
+    /// shorthand for [BuiltInOperation::Remove]
+    pub fn remove(self, attribute: &str, value: impl Into<Value>) -> Self {
+        self.operation(attribute, BuiltInOperation::Remove, value)
+    }
+
+    /// shorthand for [BuiltInOperation::IncrementFrom]
+    pub fn increment_from(self, attribute: &str, value: i64) -> Self {
+        self.operation(attribute, BuiltInOperation::IncrementFrom, value)
+    }
+
+    /// shorthand for [BuiltInOperation::IncrementSet]
+    pub fn increment_set(self, attribute: &str, value: i64) -> Self {
+        self.operation(attribute, BuiltInOperation::IncrementSet, value)
+    }
+
+    /// whether the document is created when it does not exist, defaults to true
+    pub fn create_if_not_exists(mut self, create: bool) -> Self {
+        self.create_if_not_exists = create;
+        self
+    }
+
+    fn request(
+        &self,
+        index: &Index,
+        object_id: &ObjectId,
+    ) -> Result<AlgoliaRequest, EasyAlgoliaError> {
+        let path = format!("{}/partial", object_path(index, &object_id.obj_id));
+        AlgoliaRequest::write(Method::POST, path)
+            .query("createIfNotExists", &self.create_if_not_exists.to_string())
+            .json(&self.attributes)
+    }
+}
+
+impl Client {
+    /// update some attributes of a document, other attributes are left untouched
+    /// # Examples
+    /// ```ignore
+    ///    let update = PartialUpdate::new().increment("views", 1);
+    ///    client.partial_update_async(&index, "last-of-us".into(), update).await?;
+    /// ```
+    pub async fn partial_update_async(
+        &self,
+        index: &Index,
+        object_id: ObjectId,
+        update: PartialUpdate,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request(update.request(index, &object_id)?).await
+    }
+
+    /// same as [partial_update_async](crate::Client::partial_update_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn partial_update(
+        &self,
+        index: &Index,
+        object_id: ObjectId,
+        update: PartialUpdate,
+    ) -> Result<WriteResponse, EasyAlgoliaError> {
+        self.send_request_blocking(update.request(index, &object_id)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_partial_update_body() {
+        let update = PartialUpdate::new()
+            .set("title", "Halo")
+            .increment("views", 1)
+            .decrement("stock", 2)
+            .add("tags", "fps")
+            .add_unique("platforms", "xbox")
+            .remove("tags", "beta")
+            .increment_from("version", 3)
+            .increment_set("last_seen", 1700000000);
+        assert_eq!(
+            Value::Object(update.attributes),
+            serde_json::json!({
+                "title": "Halo",
+                "views": { "_operation": "Increment", "value": 1 },
+                "stock": { "_operation": "Decrement", "value": 2 },
+                "tags": { "_operation": "Remove", "value": "beta" },
+                "platforms": { "_operation": "AddUnique", "value": "xbox" },
+                "version": { "_operation": "IncrementFrom", "value": 3 },
+                "last_seen": { "_operation": "IncrementSet", "value": 1700000000 },
+            })
+        );
+        assert!(update.create_if_not_exists);
+    }
+}
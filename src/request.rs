@@ -0,0 +1,102 @@
+//! Description of a request to Algolia
+//! requests are described once and sent to as many hosts as needed by the
+//! [RetryStrategy](crate::retry::RetryStrategy)
+use crate::{
+    error::{
+        EasyAlgoliaError,
+        ErrorKind,
+    },
+    retry::CallKind,
+};
+use http::Method;
+use serde::Serialize;
+
+/// method, path and body of a request, without the host
+#[derive(Debug, Clone)]
+pub(crate) struct AlgoliaRequest {
+    pub(crate) method: Method,
+    /// path and query string, starts with `/1/`
+    pub(crate) path: String,
+    pub(crate) body: Option<Vec<u8>>,
+    pub(crate) call: CallKind,
+}
+
+impl AlgoliaRequest {
+    fn new(method: Method, path: String, call: CallKind) -> Self {
+        Self {
+            method,
+            path,
+            body: None,
+            call,
+        }
+    }
+
+    /// request fetching data, sent to the read hosts
+    pub(crate) fn read(method: Method, path: String) -> Self {
+        Self::new(method, path, CallKind::Read)
+    }
+
+    /// request changing an index, sent to the write hosts
+    pub(crate) fn write(method: Method, path: String) -> Self {
+        Self::new(method, path, CallKind::Write)
+    }
+
+    /// append a url encoded query param to the path
+    pub(crate) fn query(mut self, key: &str, value: &str) -> Self {
+        let separator = if self.path.contains('?') { '&' } else { '?' };
+        self.path.push(separator);
+        self.path.push_str(
+            &form_urlencoded::Serializer::new(String::new())
+                .append_pair(key, value)
+                .finish(),
+        );
+        self
+    }
+
+    /// set the json body of the request
+    pub(crate) fn json<B>(mut self, body: &B) -> Result<Self, EasyAlgoliaError>
+    where
+        B: Serialize + ?Sized,
+    {
+        let body = serde_json::to_vec(body).map_err(|err| {
+            EasyAlgoliaError::new(
+                ErrorKind::InvalidRequest,
+                Some(format!("failed to serialize request body: {err}")),
+            )
+        })?;
+        self.body = Some(body);
+        Ok(self)
+    }
+
+    /// whether sending the request twice has the same effect as sending it once
+    /// reads are sent with POST for search and browse, writes are idempotent unless they are POST
+    pub(crate) fn is_idempotent(&self) -> bool {
+        self.call == CallKind::Read || self.method != Method::POST
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_params_are_encoded() {
+        let request = AlgoliaRequest::read(Method::GET, "/1/indexes/Games/halo".into())
+            .query("attributesToRetrieve", "name,genre")
+            .query("createIfNotExists", "true");
+        assert_eq!(
+            request.path,
+            "/1/indexes/Games/halo?attributesToRetrieve=name%2Cgenre&createIfNotExists=true"
+        );
+    }
+
+    #[test]
+    fn test_idempotent_requests() {
+        let search = AlgoliaRequest::read(Method::POST, "/1/indexes/Games/query".into());
+        let update = AlgoliaRequest::write(Method::PUT, "/1/indexes/Games/halo".into());
+        let batch = AlgoliaRequest::write(Method::POST, "/1/indexes/Games/batch".into());
+        assert!(search.is_idempotent());
+        assert!(update.is_idempotent());
+        assert!(!batch.is_idempotent());
+    }
+}
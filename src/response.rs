@@ -0,0 +1,417 @@
+//! Typed responses returned by Algolia
+//! every struct in this module is deserialized from the json body Algolia sends back
+use crate::algoliaobject::AlgoliaObject;
+use serde::{
+    de::{
+        DeserializeOwned,
+        Error,
+    },
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use serde_json::{
+    Map,
+    Value,
+};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+};
+
+/// response of a search request made through
+/// [client.search_async](crate::Client::search_async)
+/// `T` is the type each hit is deserialized into, it can be your own document struct,
+/// `serde_json::Value` for raw json or [Hit](crate::response::Hit) to keep Algolia metadata
+/// ```ignore
+///    let index: Index = "Games".into();
+///    let query = SearchQuery::new().query("last of us");
+///    let response: SearchResponse<Game> = client.search_async(&index, query).await?;
+///    for game in response.hits {
+///        println!("{}", game.name);
+///    }
+/// ```
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse<T> {
+    /// documents matching the query
+    pub hits: Vec<T>,
+    /// total number of documents matching the query
+    pub nb_hits: u64,
+    /// index of the current page, starts at zero
+    pub page: u32,
+    /// total number of pages available for the query
+    pub nb_pages: u32,
+    /// max number of hits per page
+    pub hits_per_page: u32,
+    /// time taken by Algolia engine to process the query, in milliseconds
+    #[serde(rename = "processingTimeMS")]
+    pub processing_time_ms: u64,
+    /// search parameters echoed back by Algolia
+    #[serde(default)]
+    pub params: String,
+    /// count of hits per value of each facet asked with
+    /// [SearchQuery::facets](crate::algoliaobject::SearchQuery::facets)
+    #[serde(default)]
+    pub facets: HashMap<String, HashMap<String, u64>>,
+    /// id of the search when
+    /// [click_analytics](crate::algoliaobject::SearchQuery::click_analytics) is enabled
+    #[serde(default, rename = "queryID")]
+    pub query_id: Option<String>,
+}
+
+/// id of an Algolia task
+/// Algolia applies writes asynchronously, every write returns the id of the task doing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    /// raw task id as sent by Algolia
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for TaskId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// response of a single write such as
+/// [put_document_async](crate::Client::put_document_async) or
+/// [update_index_setting](crate::Client::update_index_setting)
+/// the write is applied once its task is published, see
+/// [wait_task_async](crate::Client::wait_task_async)
+#[derive(Debug, serde::Deserialize)]
+pub struct WriteResponse {
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+    /// object id of the written document, not sent for index wide writes
+    #[serde(rename = "objectID")]
+    pub object_id: Option<String>,
+    /// date of the write, Algolia names it `createdAt`, `updatedAt` or `deletedAt`
+    /// depending on the operation
+    #[serde(rename = "updatedAt", alias = "createdAt", alias = "deletedAt")]
+    pub updated_at: Option<String>,
+}
+
+/// response of a batch made through [client.batch_async](crate::Client::batch_async)
+#[derive(Debug, Default)]
+pub struct BatchResponse {
+    /// one task per batch request sent, in the order they were sent
+    pub task_ids: Vec<TaskId>,
+    /// object ids of all the written documents, in the order of the operations
+    pub object_ids: Vec<String>,
+}
+
+/// response of a multi index batch made through
+/// [client.multi_batch_async](crate::Client::multi_batch_async)
+#[derive(Debug, Default)]
+pub struct MultiBatchResponse {
+    /// last task of every index touched by the batch, keyed by index name
+    pub task_ids: HashMap<String, TaskId>,
+    /// object ids of all the written documents, in the order of the operations
+    pub object_ids: Vec<String>,
+}
+
+/// response of [client.replace_all_objects_async](crate::Client::replace_all_objects_async)
+/// every task is already published when it is returned
+#[derive(Debug)]
+pub struct ReplaceAllObjectsResponse {
+    /// copy of the index settings, synonyms and rules to the temporary index
+    pub copy_operation: WriteResponse,
+    /// writes of the documents to the temporary index
+    pub batch: BatchResponse,
+    /// move of the temporary index over the index
+    pub move_operation: WriteResponse,
+}
+
+/// a single search hit with its Algolia metadata
+/// the document itself is deserialized into `T` from the same json object, so the struct used
+/// with [put_document_async](crate::Client::put_document_async) can be read back as is
+/// ```ignore
+///    let response: SearchResponse<Hit<Game>> = client.search_async(&index, query).await?;
+///    for hit in response.hits {
+///        // Hit<T> derefs to T
+///        println!("{} {:?}", hit.name, hit.highlight_result);
+///    }
+/// ```
+#[derive(Debug)]
+pub struct Hit<T> {
+    /// document deserialized from the hit
+    pub document: T,
+    /// `objectID` of the hit
+    pub object_id: String,
+    /// highlighted attributes, keyed by attribute name
+    pub highlight_result: Option<HashMap<String, HighlightResult>>,
+    /// snippeted attributes, keyed by attribute name
+    pub snippet_result: Option<HashMap<String, SnippetResult>>,
+    /// ranking details, only present when `getRankingInfo` is enabled
+    pub ranking_info: Option<RankingInfo>,
+}
+
+impl<T> Hit<T> {
+    /// consume the hit and return the document
+    pub fn into_inner(self) -> T {
+        self.document
+    }
+}
+
+impl<T> Deref for Hit<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.document
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Hit<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut hit = Map::<String, Value>::deserialize(deserializer)?;
+        let highlight_result = take_metadata(&mut hit, "_highlightResult")?;
+        let snippet_result = take_metadata(&mut hit, "_snippetResult")?;
+        let ranking_info = take_metadata(&mut hit, "_rankingInfo")?;
+        let object_id = match hit.get("objectID") {
+            Some(Value::String(id)) => id.clone(),
+            Some(id) => id.to_string(),
+            None => String::new(),
+        };
+        // objectID stays in the map so documents which store it can still read it
+        let document = T::deserialize(Value::Object(hit)).map_err(D::Error::custom)?;
+        Ok(Self {
+            document,
+            object_id,
+            highlight_result,
+            snippet_result,
+            ranking_info,
+        })
+    }
+}
+
+/// remove Algolia metadata `key` from the hit and deserialize it
+fn take_metadata<M, E>(hit: &mut Map<String, Value>, key: &str) -> Result<Option<M>, E>
+where
+    M: DeserializeOwned,
+    E: Error,
+{
+    hit.remove(key)
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(E::custom)
+}
+
+/// only the document is serialized, Algolia metadata is read only
+impl<T> Serialize for Hit<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.document.serialize(serializer)
+    }
+}
+
+/// a hit keeps the `objectID` it was read with, so writing it back updates the same document
+impl<T> AlgoliaObject for Hit<T>
+where
+    T: Serialize,
+{
+    fn get_object_id(&self) -> String {
+        String::from(&self.object_id)
+    }
+}
+
+/// how well an attribute matched the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchLevel {
+    None,
+    Partial,
+    Full,
+}
+
+/// highlight of a single attribute value
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightField {
+    /// attribute value with matched words wrapped in highlight tags
+    pub value: String,
+    pub match_level: MatchLevel,
+    /// words of the query found in the attribute
+    #[serde(default)]
+    pub matched_words: Vec<String>,
+    /// whether the whole attribute value is highlighted
+    pub fully_highlighted: Option<bool>,
+}
+
+/// highlight result of an attribute
+/// arrays and nested objects of the document are mirrored in the highlight result
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum HighlightResult {
+    Field(HighlightField),
+    List(Vec<HighlightResult>),
+    Nested(HashMap<String, HighlightResult>),
+}
+
+/// snippet of a single attribute value
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetField {
+    /// snippeted attribute value with matched words wrapped in highlight tags
+    pub value: String,
+    pub match_level: MatchLevel,
+}
+
+/// snippet result of an attribute
+/// arrays and nested objects of the document are mirrored in the snippet result
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum SnippetResult {
+    Field(SnippetField),
+    List(Vec<SnippetResult>),
+    Nested(HashMap<String, SnippetResult>),
+}
+
+/// ranking criteria values of a hit, see Algolia ranking formula for their meaning
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RankingInfo {
+    pub nb_typos: u32,
+    pub first_matched_word: u32,
+    pub proximity_distance: u32,
+    pub user_score: i64,
+    pub geo_distance: u32,
+    pub geo_precision: Option<u32>,
+    pub nb_exact_words: u32,
+    pub words: u32,
+    pub filters: u32,
+    pub promoted: Option<bool>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search_response_deserialize() {
+        let body = serde_json::json!({
+            "hits": [{ "name": "orange", "objectID": "1" }],
+            "nbHits": 1,
+            "page": 0,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 2,
+            "exhaustiveNbHits": true,
+            "query": "orange",
+            "params": "query=orange"
+        });
+        let response: SearchResponse<serde_json::Value> = serde_json::from_value(body).unwrap();
+        assert_eq!(response.nb_hits, 1);
+        assert_eq!(response.hits_per_page, 20);
+        assert_eq!(response.processing_time_ms, 2);
+        assert_eq!(response.params, "query=orange");
+        assert_eq!(response.hits[0]["name"], "orange");
+    }
+
+    #[derive(serde::Deserialize, serde::Serialize)]
+    struct Game {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_hit_deserialize() {
+        let body = serde_json::json!({
+            "name": "Last of us",
+            "tags": ["survival"],
+            "objectID": "last-of-us",
+            "_highlightResult": {
+                "name": {
+                    "value": "<em>Last</em> of us",
+                    "matchLevel": "partial",
+                    "matchedWords": ["last"],
+                    "fullyHighlighted": false
+                },
+                "tags": [{ "value": "survival", "matchLevel": "none", "matchedWords": [] }]
+            },
+            "_snippetResult": {
+                "name": { "value": "<em>Last</em> of …", "matchLevel": "partial" }
+            },
+            "_rankingInfo": { "nbTypos": 1, "words": 2, "userScore": 7 }
+        });
+        let hit: Hit<Game> = serde_json::from_value(body).unwrap();
+        assert_eq!(hit.name, "Last of us");
+        assert_eq!(hit.get_object_id(), "last-of-us");
+        let highlight = hit.highlight_result.as_ref().unwrap();
+        match &highlight["name"] {
+            HighlightResult::Field(field) => {
+                assert_eq!(field.match_level, MatchLevel::Partial);
+                assert_eq!(field.matched_words, vec!["last"]);
+                assert_eq!(field.fully_highlighted, Some(false));
+            }
+            other => panic!("unexpected highlight {other:?}"),
+        }
+        assert!(matches!(&highlight["tags"], HighlightResult::List(tags) if tags.len() == 1));
+        let ranking = hit.ranking_info.as_ref().unwrap();
+        assert_eq!(
+            (ranking.nb_typos, ranking.words, ranking.user_score),
+            (1, 2, 7)
+        );
+        // metadata is never written back
+        let json = serde_json::to_value(&hit).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "name": "Last of us", "tags": ["survival"] })
+        );
+    }
+
+    #[test]
+    fn test_hit_keeps_object_id_for_document() {
+        #[derive(serde::Deserialize)]
+        struct WithId {
+            #[serde(rename = "objectID")]
+            id: String,
+        }
+        let body = serde_json::json!({ "objectID": "42" });
+        let hit: Hit<WithId> = serde_json::from_value(body).unwrap();
+        assert_eq!(hit.id, "42");
+        assert_eq!(hit.object_id, "42");
+        assert!(hit.highlight_result.is_none());
+    }
+
+    #[test]
+    fn test_write_response_deserialize() {
+        let created: WriteResponse = serde_json::from_value(serde_json::json!({
+            "createdAt": "2024-03-10T10:00:00.000Z",
+            "taskID": 678,
+            "objectID": "6891"
+        }))
+        .unwrap();
+        assert_eq!(created.task_id.get(), 678);
+        assert_eq!(created.object_id.as_deref(), Some("6891"));
+        assert_eq!(
+            created.updated_at.as_deref(),
+            Some("2024-03-10T10:00:00.000Z")
+        );
+
+        let deleted: WriteResponse = serde_json::from_value(serde_json::json!({
+            "deletedAt": "2024-03-10T10:00:00.000Z",
+            "taskID": 679
+        }))
+        .unwrap();
+        assert!(deleted.object_id.is_none());
+        assert!(deleted.updated_at.is_some());
+    }
+}
@@ -0,0 +1,348 @@
+//! Host selection and retries
+//! Algolia serves every application from several hosts, reads go to the `{app}-dsn` host first
+//! and writes to the `{app}` host, both fall back to the `{app}-1/2/3.algolianet.com` hosts when
+//! a host is unreachable or fails, see [RetryStrategy]
+use std::{
+    sync::Mutex,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// kind of call, reads and writes are not sent to the same hosts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    /// search, browse and every request fetching data
+    Read,
+    /// every request changing an index
+    Write,
+}
+
+/// health of a host after a request was sent to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostStatus {
+    /// the host answered, even with a 4xx error
+    Up,
+    /// the host could not be reached, timed out or failed with a 5xx error
+    Down,
+}
+
+/// decides which hosts a request is sent to, and in which order
+/// the client sends the request to the first host and moves on to the next one when the host
+/// is down, as long as the request can safely be sent again: reads and idempotent writes are
+/// retried on transport errors and 5xx errors, 4xx errors are never retried
+/// a write not known to be idempotent is only retried when the connection could not be
+/// established, since Algolia never received it
+/// # Examples
+/// ```
+/// use EasyAlgolia::{
+///     client_builder::ClientBuilder,
+///     retry::{
+///         CallKind,
+///         HostStatus,
+///         RetryStrategy,
+///     },
+/// };
+/// // always go through a single proxy
+/// struct Proxy;
+/// impl RetryStrategy for Proxy {
+///     fn hosts(&self, _call: CallKind) -> Vec<String> {
+///         vec!["https://algolia-proxy.internal".into()]
+///     }
+///     fn report(&self, _host: &str, _status: HostStatus) {}
+/// }
+/// let client = ClientBuilder::new()
+///     .set_application_id("123")
+///     .set_api_key("123")
+///     .set_retry_strategy(Proxy)
+///     .build();
+/// ```
+pub trait RetryStrategy: Send + Sync {
+    /// base urls to try for a call in order, such as `https://{app}-dsn.algolia.net`
+    fn hosts(&self, call: CallKind) -> Vec<String>;
+
+    /// record the health of `host` after a request was sent to it
+    fn report(&self, host: &str, status: HostStatus);
+}
+
+/// protocol used to reach a [Host]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scheme {
+    Http,
+    #[default]
+    Https,
+}
+
+/// a host requests can be sent to, such as a local stand-in for Algolia or a proxy
+/// hosts accept both reads and writes on the default port of the scheme unless told otherwise
+/// # Examples
+/// ```
+/// use EasyAlgolia::retry::{
+///     Host,
+///     Scheme,
+/// };
+/// let local = Host::new("localhost").scheme(Scheme::Http).port(8080);
+/// assert_eq!(local.url(), "http://localhost:8080");
+/// let replica = Host::new("search.internal").read_only();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    name: String,
+    scheme: Scheme,
+    port: Option<u16>,
+    read: bool,
+    write: bool,
+}
+
+impl Host {
+    /// https host accepting reads and writes
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            scheme: Scheme::default(),
+            port: None,
+            read: true,
+            write: true,
+        }
+    }
+
+    /// protocol of the host, defaults to https
+    pub fn scheme(mut self, scheme: Scheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// port of the host, defaults to the port of the scheme
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// only send reads to this host
+    pub fn read_only(mut self) -> Self {
+        self.read = true;
+        self.write = false;
+        self
+    }
+
+    /// only send writes to this host
+    pub fn write_only(mut self) -> Self {
+        self.read = false;
+        self.write = true;
+        self
+    }
+
+    /// whether the host accepts the given kind of call
+    pub fn accepts(&self, call: CallKind) -> bool {
+        match call {
+            CallKind::Read => self.read,
+            CallKind::Write => self.write,
+        }
+    }
+
+    /// base url of the host, as given to [RetryStrategy::report]
+    pub fn url(&self) -> String {
+        let scheme = match self.scheme {
+            Scheme::Http => "http",
+            Scheme::Https => "https",
+        };
+        match self.port {
+            Some(port) => format!("{scheme}://{}:{port}", self.name),
+            None => format!("{scheme}://{}", self.name),
+        }
+    }
+}
+
+/// hosts are considered down for 2 minutes before being tried again
+pub const DEFAULT_HOST_TTL: Duration = Duration::from_secs(120);
+
+#[derive(Debug)]
+struct StatefulHost {
+    host: Host,
+    url: String,
+    down_since: Option<Instant>,
+}
+
+impl StatefulHost {
+    fn new(host: Host) -> Self {
+        Self {
+            url: host.url(),
+            host,
+            down_since: None,
+        }
+    }
+
+    fn accepts(&self, call: CallKind) -> bool {
+        self.host.accepts(call)
+    }
+}
+
+/// retry strategy used by default, tracks the health of the Algolia hosts of the application
+/// a host reported down is skipped until `ttl` is spent, if every host is down they are all
+/// tried again
+#[derive(Debug)]
+pub struct DefaultRetryStrategy {
+    hosts: Mutex<Vec<StatefulHost>>,
+    ttl: Duration,
+}
+
+impl DefaultRetryStrategy {
+    /// hosts of the given application, `{app}-dsn.algolia.net` for reads,
+    /// `{app}.algolia.net` for writes and `{app}-1/2/3.algolianet.com` as fallback for both
+    pub fn new(application_id: &str) -> Self {
+        let mut hosts = vec![
+            Host::new(&format!("{application_id}-dsn.algolia.net")).read_only(),
+            Host::new(&format!("{application_id}.algolia.net")).write_only(),
+        ];
+        hosts.extend((1..=3).map(|n| Host::new(&format!("{application_id}-{n}.algolianet.com"))));
+        Self::with_hosts(hosts)
+    }
+
+    /// the given hosts, tried in order for the calls they accept
+    /// # Examples
+    /// ```
+    /// use EasyAlgolia::retry::{
+    ///     DefaultRetryStrategy,
+    ///     Host,
+    ///     Scheme,
+    /// };
+    /// let strategy = DefaultRetryStrategy::with_hosts(vec![Host::new("localhost")
+    ///     .scheme(Scheme::Http)
+    ///     .port(8080)]);
+    /// ```
+    pub fn with_hosts(hosts: Vec<Host>) -> Self {
+        Self::from_hosts(hosts.into_iter().map(StatefulHost::new).collect())
+    }
+
+    fn from_hosts(hosts: Vec<StatefulHost>) -> Self {
+        Self {
+            hosts: Mutex::new(hosts),
+            ttl: DEFAULT_HOST_TTL,
+        }
+    }
+
+    /// how long a host reported down is skipped, defaults to [DEFAULT_HOST_TTL]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+impl RetryStrategy for DefaultRetryStrategy {
+    fn hosts(&self, call: CallKind) -> Vec<String> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        for host in hosts.iter_mut() {
+            if host
+                .down_since
+                .is_some_and(|since| since.elapsed() >= self.ttl)
+            {
+                host.down_since = None;
+            }
+        }
+        let up: Vec<String> = hosts
+            .iter()
+            .filter(|host| host.accepts(call) && host.down_since.is_none())
+            .map(|host| host.url.clone())
+            .collect();
+        if !up.is_empty() {
+            return up;
+        }
+        // every host is down, trying them again beats failing without sending anything
+        hosts
+            .iter_mut()
+            .filter(|host| host.accepts(call))
+            .map(|host| {
+                host.down_since = None;
+                host.url.clone()
+            })
+            .collect()
+    }
+
+    fn report(&self, host: &str, status: HostStatus) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(host) = hosts.iter_mut().find(|h| h.url == host) {
+            host.down_since = match status {
+                HostStatus::Up => None,
+                HostStatus::Down => Some(Instant::now()),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_hosts_split_reads_and_writes() {
+        let strategy = DefaultRetryStrategy::new("APP");
+        assert_eq!(
+            strategy.hosts(CallKind::Read),
+            vec![
+                "https://APP-dsn.algolia.net",
+                "https://APP-1.algolianet.com",
+                "https://APP-2.algolianet.com",
+                "https://APP-3.algolianet.com",
+            ]
+        );
+        assert_eq!(
+            strategy.hosts(CallKind::Write)[0],
+            "https://APP.algolia.net"
+        );
+        assert_eq!(strategy.hosts(CallKind::Write).len(), 4);
+    }
+
+    #[test]
+    fn test_host_url() {
+        assert_eq!(
+            Host::new("APP.algolia.net").url(),
+            "https://APP.algolia.net"
+        );
+        let local = Host::new("127.0.0.1")
+            .scheme(Scheme::Http)
+            .port(8080)
+            .write_only();
+        assert_eq!(local.url(), "http://127.0.0.1:8080");
+        assert!(!local.accepts(CallKind::Read));
+    }
+
+    #[test]
+    fn test_down_host_is_skipped_until_ttl() {
+        let strategy = DefaultRetryStrategy::new("APP");
+        strategy.report("https://APP-dsn.algolia.net", HostStatus::Down);
+        assert_eq!(
+            strategy.hosts(CallKind::Read)[0],
+            "https://APP-1.algolianet.com"
+        );
+        // writes do not use the dsn host and are not affected
+        assert_eq!(
+            strategy.hosts(CallKind::Write)[0],
+            "https://APP.algolia.net"
+        );
+
+        let strategy = DefaultRetryStrategy::new("APP").with_ttl(Duration::ZERO);
+        strategy.report("https://APP-dsn.algolia.net", HostStatus::Down);
+        assert_eq!(
+            strategy.hosts(CallKind::Read)[0],
+            "https://APP-dsn.algolia.net"
+        );
+    }
+
+    #[test]
+    fn test_every_host_down_resets_them() {
+        let strategy = DefaultRetryStrategy::with_hosts(vec![
+            Host::new("a").scheme(Scheme::Http),
+            Host::new("b").scheme(Scheme::Http),
+        ]);
+        strategy.report("http://a", HostStatus::Down);
+        strategy.report("http://b", HostStatus::Down);
+        assert_eq!(strategy.hosts(CallKind::Read), vec!["http://a", "http://b"]);
+        strategy.report("http://a", HostStatus::Down);
+        strategy.report("http://a", HostStatus::Up);
+        assert_eq!(
+            strategy.hosts(CallKind::Write),
+            vec!["http://a", "http://b"]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Algolia task tracking
+//! writes are applied asynchronously by Algolia, every write returns a
+//! [TaskId](crate::response::TaskId) which can be waited on until the write is searchable
+use crate::{
+    error::{
+        EasyAlgoliaError,
+        ErrorKind,
+    },
+    request::AlgoliaRequest,
+    response::TaskId,
+    Client,
+    Index,
+};
+use http::Method;
+use std::time::Duration;
+
+/// status of an Algolia task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    /// the write is applied and visible to searches
+    Published,
+    /// the write is still queued or being applied
+    NotPublished,
+}
+
+#[derive(serde::Deserialize)]
+struct TaskResponse {
+    status: TaskStatus,
+}
+
+/// polling policy of [wait_task_async](crate::Client::wait_task_async)
+/// the delay between two polls starts at `initial_delay` and doubles up to `max_delay`, waiting
+/// stops with an error once `timeout` is spent
+/// ```
+/// use std::time::Duration;
+/// use EasyAlgolia::task::TaskWaitPolicy;
+/// let policy = TaskWaitPolicy {
+///     max_delay: Duration::from_secs(1),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaskWaitPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for TaskWaitPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+impl TaskWaitPolicy {
+    /// delays to sleep between polls, ends when the next delay would exceed the timeout
+    fn delays(&self) -> impl Iterator<Item = Duration> {
+        let (max_delay, timeout) = (self.max_delay, self.timeout);
+        let mut next = self.initial_delay;
+        let mut spent = Duration::ZERO;
+        std::iter::from_fn(move || {
+            // a zero delay would poll forever without ever reaching the timeout
+            let delay = next.min(max_delay).max(Duration::from_millis(1));
+            spent += delay;
+            next = delay.saturating_mul(2);
+            (spent <= timeout).then_some(delay)
+        })
+    }
+}
+
+fn task_request(index: &Index, task_id: TaskId) -> AlgoliaRequest {
+    let path = format!("/1/indexes/{}/task/{}", index.index(), task_id.get());
+    AlgoliaRequest::read(Method::GET, path)
+}
+
+fn task_timeout(task_id: TaskId) -> EasyAlgoliaError {
+    EasyAlgoliaError::new(
+        ErrorKind::Timeout,
+        Some(format!(
+            "task {} was not published before the wait timeout",
+            task_id.get()
+        )),
+    )
+}
+
+impl Client {
+    /// get the current status of a task
+    pub async fn get_task_status_async(
+        &self,
+        index: &Index,
+        task_id: TaskId,
+    ) -> Result<TaskStatus, EasyAlgoliaError> {
+        let task: TaskResponse = self.send_request(task_request(index, task_id)).await?;
+        Ok(task.status)
+    }
+
+    /// same as [get_task_status_async](crate::Client::get_task_status_async) but blocking in nature
+    /// the request is sent by a blocking http client, no async runtime is needed
+    #[cfg(feature = "blocking")]
+    pub fn get_task_status(
+        &self,
+        index: &Index,
+        task_id: TaskId,
+    ) -> Result<TaskStatus, EasyAlgoliaError> {
+        let task: TaskResponse = self.send_request_blocking(task_request(index, task_id))?;
+        Ok(task.status)
+    }
+
+    /// wait until the task is published, polling Algolia as configured by the
+    /// [TaskWaitPolicy](crate::task::TaskWaitPolicy) of the client
+    /// # Examples
+    /// ```ignore
+    ///    let response = client.put_document_async(&index, &doc).await?;
+    ///    client.wait_task_async(&index, response.task_id).await?;
+    ///    // doc is now searchable
+    /// ```
+    /// # Error
+    /// returns [`Err(EasyAlgoliaError)`](crate::error::EasyAlgoliaError) if the task is still not
+    /// published when the policy timeout is reached
+    pub async fn wait_task_async(
+        &self,
+        index: &Index,
+        task_id: TaskId,
+    ) -> Result<(), EasyAlgoliaError> {
+        let mut delays = self.task_wait_policy.delays();
+        loop {
+            if self.get_task_status_async(index, task_id).await? == TaskStatus::Published {
+                return Ok(());
+            }
+            match delays.next() {
+                // the timer runs on its own thread, any executor can drive the wait
+                Some(delay) => futures_timer::Delay::new(delay).await,
+                None => return Err(task_timeout(task_id)),
+            }
+        }
+    }
+
+    /// same as [wait_task_async](crate::Client::wait_task_async) but blocking in nature
+    /// the thread sleeps between two polls
+    #[cfg(feature = "blocking")]
+    pub fn wait_task(&self, index: &Index, task_id: TaskId) -> Result<(), EasyAlgoliaError> {
+        let mut delays = self.task_wait_policy.delays();
+        loop {
+            if self.get_task_status(index, task_id)? == TaskStatus::Published {
+                return Ok(());
+            }
+            match delays.next() {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(task_timeout(task_id)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        client_builder::ClientBuilder,
+        transport::{
+            HttpResponse,
+            InMemoryRequester,
+        },
+    };
+    use http::StatusCode;
+
+    #[test]
+    fn test_wait_policy_delays() {
+        let policy = TaskWaitPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(400),
+            timeout: Duration::from_millis(1500),
+        };
+        let delays: Vec<u64> = policy.delays().map(|d| d.as_millis() as u64).collect();
+        assert_eq!(delays, vec![100, 200, 400, 400, 400]);
+    }
+
+    #[test]
+    fn test_wait_task_without_runtime() {
+        let requester = InMemoryRequester::new();
+        for status in ["notPublished", "published"] {
+            requester.push_response(HttpResponse::new(
+                StatusCode::OK,
+                format!(r#"{{ "status": "{status}" }}"#),
+            ));
+        }
+        let client = ClientBuilder::new()
+            .set_application_id("APP")
+            .set_api_key("secret")
+            .set_http_requester(requester.clone())
+            .set_task_wait_policy(TaskWaitPolicy {
+                initial_delay: Duration::from_millis(1),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        futures::executor::block_on(client.wait_task_async(&"Games".into(), TaskId::from(7)))
+            .unwrap();
+        assert_eq!(requester.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_task_status_deserialize() {
+        let task: TaskResponse =
+            serde_json::from_str(r#"{ "status": "notPublished", "pendingTask": true }"#).unwrap();
+        assert_eq!(task.status, TaskStatus::NotPublished);
+    }
+}
@@ -0,0 +1,1486 @@
+//! In-memory stand-in for Algolia, for tests
+//! [MockAlgolia] listens on localhost and serves the endpoints used by the
+//! [Client](crate::Client): objects, batches, settings, search, browse, index operations and
+//! tasks, so the client can be tested end to end without reaching Algolia
+//! only available with the `testing` feature
+use crate::{
+    client_builder::ClientBuilder,
+    retry::{
+        Host,
+        Scheme,
+    },
+    Client,
+};
+use http::{
+    header::{
+        HeaderMap,
+        HeaderName,
+        HeaderValue,
+    },
+    Method,
+    StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use serde_json::{
+    json,
+    Map,
+    Value,
+};
+use std::{
+    collections::BTreeMap,
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        Write,
+    },
+    net::{
+        SocketAddr,
+        TcpListener,
+        TcpStream,
+    },
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+};
+
+/// application id of the clients built by [MockAlgolia::client]
+pub const MOCK_APPLICATION_ID: &str = "MOCKAPP";
+/// api key of the clients built by [MockAlgolia::client]
+pub const MOCK_API_KEY: &str = "mock-api-key";
+
+/// request received by a [MockAlgolia]
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub method: Method,
+    /// path without the query string, such as `/1/indexes/Games/batch`
+    pub path: String,
+    /// decoded query string params
+    pub query: Vec<(String, String)>,
+    pub headers: HeaderMap,
+    /// json body, `Value::Null` when the request has none
+    pub body: Value,
+}
+
+#[derive(Default)]
+struct MockIndex {
+    /// documents in insertion order, each holds its `objectID`
+    objects: Vec<Map<String, Value>>,
+    settings: Map<String, Value>,
+}
+
+impl MockIndex {
+    fn position(&self, object_id: &str) -> Option<usize> {
+        self.objects
+            .iter()
+            .position(|object| object["objectID"] == object_id)
+    }
+
+    fn get(&self, object_id: &str) -> Option<&Map<String, Value>> {
+        self.position(object_id)
+            .map(|position| &self.objects[position])
+    }
+
+    /// replace the document having the same object id, or append it
+    fn put(&mut self, object: Map<String, Value>) {
+        let object_id = object["objectID"].as_str().unwrap_or_default();
+        match self.position(object_id) {
+            Some(position) => self.objects[position] = object,
+            None => self.objects.push(object),
+        }
+    }
+
+    fn delete(&mut self, object_id: &str) {
+        self.objects
+            .retain(|object| object["objectID"] != object_id);
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    indexes: BTreeMap<String, MockIndex>,
+    requests: Vec<ReceivedRequest>,
+    last_task: u64,
+    last_object: u64,
+}
+
+impl MockState {
+    /// every write is applied right away, its task is published as soon as it is created
+    fn next_task(&mut self) -> u64 {
+        self.last_task += 1;
+        self.last_task
+    }
+
+    /// object id given to documents written without one
+    fn next_object_id(&mut self) -> String {
+        self.last_object += 1;
+        format!("mock-{}", self.last_object)
+    }
+
+    fn index(&mut self, name: &str) -> &mut MockIndex {
+        self.indexes.entry(name.into()).or_default()
+    }
+}
+
+/// http server standing in for Algolia, every index lives in memory
+/// writes are applied before answering and their tasks are always published, search and
+/// browse match documents containing every word of the query as a substring and support the
+/// facet, numeric, range, `NOT`, `AND`, `OR` and parentheses syntax of Algolia filters
+/// the server stops when the mock is dropped
+/// # Examples
+/// ```
+/// use EasyAlgolia::{
+///     testing::MockAlgolia,
+///     Index,
+///     ObjectId,
+/// };
+/// let algolia = MockAlgolia::start().unwrap();
+/// algolia.add_objects(
+///     "Games",
+///     vec![serde_json::json!({ "objectID": "halo", "name": "Halo" })],
+/// );
+/// # #[cfg(all(feature = "reqwest", feature = "blocking"))]
+/// # {
+/// let client = algolia.client();
+/// let index: Index = "Games".into();
+/// client
+///     .delete_document(&index, ObjectId::from("halo"))
+///     .unwrap();
+/// assert!(algolia.objects("Games").is_empty());
+/// assert_eq!(algolia.requests()[0].path, "/1/indexes/Games/halo");
+/// # }
+/// ```
+pub struct MockAlgolia {
+    address: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockAlgolia {
+    /// start a server on a free localhost port
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (server_state, server_shutdown) = (Arc::clone(&state), Arc::clone(&shutdown));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let state = Arc::clone(&server_state);
+                // connections are kept alive by the client, each one gets its own thread
+                std::thread::spawn(move || {
+                    // the connection is closed on any io error, there is nobody to report it to
+                    let _ = serve_connection(stream, &state);
+                });
+            }
+        });
+        Ok(Self {
+            address,
+            state,
+            shutdown,
+        })
+    }
+
+    /// host of the server, to build a client with custom settings through
+    /// [hosts](crate::client_builder::ClientBuilder::hosts)
+    pub fn host(&self) -> Host {
+        Host::new(&self.address.ip().to_string())
+            .scheme(Scheme::Http)
+            .port(self.address.port())
+    }
+
+    /// builder sending every request to the server, with [MOCK_APPLICATION_ID] and
+    /// [MOCK_API_KEY] as credentials
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new()
+            .set_application_id(MOCK_APPLICATION_ID)
+            .set_api_key(MOCK_API_KEY)
+            .hosts(vec![self.host()])
+    }
+
+    /// client sending every request to the server
+    /// # Panics
+    /// panics if the client can not be built, such as when the `reqwest` feature is disabled,
+    /// use [client_builder](MockAlgolia::client_builder) to give it an http requester
+    pub fn client(&self) -> Client {
+        self.client_builder()
+            .build()
+            .expect("failed to build a client for the mock server")
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        lock(&self.state)
+    }
+
+    /// seed an index with documents, documents without `objectID` get one generated
+    /// documents which are not json objects are ignored
+    pub fn add_objects(&self, index: &str, objects: impl IntoIterator<Item = Value>) {
+        let mut state = self.state();
+        for object in objects {
+            if let Value::Object(object) = object {
+                let object = with_object_id(&mut state, object, None);
+                state.index(index).put(object);
+            }
+        }
+    }
+
+    /// documents of an index in insertion order, empty if the index does not exist
+    pub fn objects(&self, index: &str) -> Vec<Value> {
+        self.state()
+            .indexes
+            .get(index)
+            .map(|index| index.objects.iter().cloned().map(Value::Object).collect())
+            .unwrap_or_default()
+    }
+
+    /// settings written to an index, `None` if the index does not exist
+    pub fn settings(&self, index: &str) -> Option<Value> {
+        self.state()
+            .indexes
+            .get(index)
+            .map(|index| Value::Object(index.settings.clone()))
+    }
+
+    /// names of the existing indexes, sorted
+    pub fn indexes(&self) -> Vec<String> {
+        self.state().indexes.keys().cloned().collect()
+    }
+
+    /// every request received so far, in the order they were received
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// forget the requests received so far
+    pub fn clear_requests(&self) {
+        self.state().requests.clear();
+    }
+}
+
+impl Drop for MockAlgolia {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake the listener up so it sees the shutdown flag
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+fn lock(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    state.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// answer the requests of a connection until the client closes it
+fn serve_connection(stream: TcpStream, state: &Mutex<MockState>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    while let Some(request) = read_request(&mut reader)? {
+        let (status, body) = match request {
+            Ok(request) => {
+                let mut state = lock(state);
+                state.requests.push(request.clone());
+                handle(&mut state, &request)
+            }
+            Err(message) => error(StatusCode::BAD_REQUEST, &message),
+        };
+        let body = body.to_string();
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=UTF-8\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or_default(),
+            body.len(),
+        )?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// read the next request of the connection, `None` once the client closed it
+/// requests which can not be understood are given back as an error message
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Result<ReceivedRequest, String>>> {
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line)? == 0 {
+        return Ok(None);
+    }
+    let mut headers = HeaderMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.trim().as_bytes()),
+                HeaderValue::from_str(value.trim()),
+            ) {
+                headers.append(name, value);
+            }
+        }
+    }
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.to_str().ok()?.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(Some(Err(format!("invalid request line {request_line}"))));
+    };
+    let Ok(method) = Method::from_bytes(method.as_bytes()) else {
+        return Ok(Some(Err(format!("invalid method {method}"))));
+    };
+    let body = match body.is_empty() {
+        true => Value::Null,
+        false => match serde_json::from_slice(&body) {
+            Ok(body) => body,
+            Err(err) => return Ok(Some(Err(format!("invalid json body: {err}")))),
+        },
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Ok(Some(Ok(ReceivedRequest {
+        method,
+        path: path.into(),
+        query: form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        headers,
+        body,
+    })))
+}
+
+fn error(status: StatusCode, message: &str) -> (StatusCode, Value) {
+    (
+        status,
+        json!({ "message": message, "status": status.as_u16() }),
+    )
+}
+
+fn ok(body: Value) -> (StatusCode, Value) {
+    (StatusCode::OK, body)
+}
+
+/// answer a request, writes are applied on `state`
+fn handle(state: &mut MockState, request: &ReceivedRequest) -> (StatusCode, Value) {
+    let has_credentials = ["x-algolia-application-id", "x-algolia-api-key"]
+        .iter()
+        .all(|name| request.headers.contains_key(*name));
+    if !has_credentials {
+        return error(StatusCode::FORBIDDEN, "Invalid Application-ID or API key");
+    }
+    let Some(path) = request.path.strip_prefix("/1/indexes") else {
+        return error(StatusCode::NOT_FOUND, "Not found");
+    };
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let body = &request.body;
+    match (&request.method, segments.as_slice()) {
+        (&Method::GET, []) => list_indexes(state),
+        (&Method::POST, ["*", "objects"]) => get_objects(state, body),
+        (&Method::POST, ["*", "batch"]) => multi_batch(state, body),
+        (&Method::POST, [index]) => {
+            let Value::Object(object) = body.clone() else {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    "the document must be a json object",
+                );
+            };
+            let object = with_object_id(state, object, None);
+            let object_id = object["objectID"].clone();
+            state.index(index).put(object);
+            ok(json!({ "taskID": state.next_task(), "objectID": object_id }))
+        }
+        (&Method::DELETE, [index]) => {
+            state.indexes.remove(*index);
+            ok(json!({ "taskID": state.next_task() }))
+        }
+        (&Method::POST, [index, "batch"]) => batch(state, index, body),
+        (&Method::POST, [index, "query"]) => search(state, index, body),
+        (&Method::POST, [index, "browse"]) => browse(state, index, body),
+        (&Method::POST, [index, "deleteByQuery"]) => delete_by(state, index, body),
+        (&Method::POST, [index, "clear"]) => {
+            if let Some(index) = state.indexes.get_mut(*index) {
+                index.objects.clear();
+            }
+            ok(json!({ "taskID": state.next_task() }))
+        }
+        (&Method::POST, [index, "operation"]) => index_operation(state, index, body),
+        (&Method::GET, [index, "settings"]) => match state.indexes.get(*index) {
+            Some(index) => ok(settings(&index.settings)),
+            None => error(StatusCode::NOT_FOUND, "Index does not exist"),
+        },
+        (&Method::PUT, [index, "settings"]) => {
+            let Value::Object(settings) = body else {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    "the settings must be a json object",
+                );
+            };
+            let index = state.index(index);
+            index.settings.extend(settings.clone());
+            ok(json!({ "taskID": state.next_task() }))
+        }
+        (&Method::GET, [_, "task", task_id]) => match task_id.parse::<u64>() {
+            Ok(task_id) if task_id > 0 && task_id <= state.last_task => {
+                ok(json!({ "status": "published", "pendingTask": false }))
+            }
+            _ => error(StatusCode::NOT_FOUND, "Task does not exist"),
+        },
+        (&Method::GET, [index, object_id]) => {
+            let object = state
+                .indexes
+                .get(*index)
+                .and_then(|index| index.get(object_id));
+            match object {
+                Some(object) => {
+                    let attributes: Option<Vec<String>> =
+                        query_param(request, "attributesToRetrieve").map(|attributes| {
+                            attributes.split(',').map(|a| a.trim().into()).collect()
+                        });
+                    ok(retrieve(object, attributes.as_deref()))
+                }
+                None => error(StatusCode::NOT_FOUND, "ObjectID does not exist"),
+            }
+        }
+        (&Method::PUT, [index, object_id]) => {
+            let Value::Object(object) = body.clone() else {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    "the document must be a json object",
+                );
+            };
+            let object = with_object_id(state, object, Some(object_id));
+            state.index(index).put(object);
+            ok(json!({ "taskID": state.next_task(), "objectID": object_id }))
+        }
+        (&Method::DELETE, [index, object_id]) => {
+            if let Some(index) = state.indexes.get_mut(*index) {
+                index.delete(object_id);
+            }
+            ok(json!({ "taskID": state.next_task() }))
+        }
+        (&Method::POST, [index, object_id, "partial"]) => {
+            let create = query_param(request, "createIfNotExists") != Some("false");
+            match partial_update(state, index, object_id, body, create) {
+                Ok(()) => ok(json!({ "taskID": state.next_task(), "objectID": object_id })),
+                Err(message) => error(StatusCode::BAD_REQUEST, &message),
+            }
+        }
+        _ => error(StatusCode::NOT_FOUND, "Not found"),
+    }
+}
+
+fn query_param<'a>(request: &'a ReceivedRequest, key: &str) -> Option<&'a str> {
+    request
+        .query
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.as_str())
+}
+
+/// set the object id of a document, generate one if it has none
+fn with_object_id(
+    state: &mut MockState,
+    mut object: Map<String, Value>,
+    object_id: Option<&str>,
+) -> Map<String, Value> {
+    let object_id = match (object_id, object.get("objectID")) {
+        (Some(object_id), _) => object_id.to_string(),
+        (None, Some(Value::String(object_id))) => object_id.clone(),
+        (None, Some(object_id)) => object_id.to_string(),
+        (None, None) => state.next_object_id(),
+    };
+    object.insert("objectID".into(), Value::String(object_id));
+    object
+}
+
+fn list_indexes(state: &MockState) -> (StatusCode, Value) {
+    let items: Vec<Value> = state
+        .indexes
+        .iter()
+        .map(|(name, index)| {
+            json!({
+                "name": name,
+                "entries": index.objects.len(),
+                "dataSize": index
+                    .objects
+                    .iter()
+                    .map(|object| serde_json::to_vec(object).map_or(0, |object| object.len()))
+                    .sum::<usize>(),
+                "pendingTask": false,
+            })
+        })
+        .collect();
+    ok(json!({ "items": items, "nbPages": 1 }))
+}
+
+/// index settings as sent back by Algolia, the written settings over the default ones
+fn settings(written: &Map<String, Value>) -> Value {
+    let mut settings = match serde_json::to_value(crate::AlgoliaIndexSetting::default()) {
+        Ok(Value::Object(settings)) => settings,
+        _ => Map::new(),
+    };
+    settings.extend(written.clone());
+    Value::Object(settings)
+}
+
+fn get_objects(state: &MockState, body: &Value) -> (StatusCode, Value) {
+    let Some(requests) = body["requests"].as_array() else {
+        return error(StatusCode::BAD_REQUEST, "requests must be an array");
+    };
+    let results: Vec<Value> = requests
+        .iter()
+        .map(|request| {
+            let object_id = request["objectID"].as_str().unwrap_or_default();
+            request["indexName"]
+                .as_str()
+                .and_then(|index| state.indexes.get(index))
+                .and_then(|index| index.get(object_id))
+                .map_or(Value::Null, |object| Value::Object(object.clone()))
+        })
+        .collect();
+    ok(json!({ "results": results }))
+}
+
+/// apply a single batch operation, returns the object id it touched
+fn apply_operation(
+    state: &mut MockState,
+    index: &str,
+    operation: &Value,
+) -> Result<Option<String>, String> {
+    let action = operation["action"].as_str().unwrap_or_default();
+    let Value::Object(body) = operation["body"].clone() else {
+        return Err(format!("the body of {action} must be a json object"));
+    };
+    let object_id = body
+        .get("objectID")
+        .and_then(Value::as_str)
+        .map(String::from);
+    match (action, object_id) {
+        ("addObject", _) => {
+            let object = with_object_id(state, body, None);
+            let object_id = object["objectID"].as_str().map(String::from);
+            state.index(index).put(object);
+            Ok(object_id)
+        }
+        ("updateObject", Some(object_id)) => {
+            let object = with_object_id(state, body, Some(&object_id));
+            state.index(index).put(object);
+            Ok(Some(object_id))
+        }
+        ("partialUpdateObject" | "partialUpdateObjectNoCreate", Some(object_id)) => {
+            let create = action == "partialUpdateObject";
+            partial_update(state, index, &object_id, &Value::Object(body), create)?;
+            Ok(Some(object_id))
+        }
+        ("deleteObject", Some(object_id)) => {
+            if let Some(index) = state.indexes.get_mut(index) {
+                index.delete(&object_id);
+            }
+            Ok(Some(object_id))
+        }
+        ("clear", _) => {
+            state.index(index).objects.clear();
+            Ok(None)
+        }
+        (action, None) if action.ends_with("Object") => {
+            Err(format!("objectID is required for {action}"))
+        }
+        (action, _) => Err(format!("unknown batch action {action}")),
+    }
+}
+
+fn batch_requests(body: &Value) -> Result<&Vec<Value>, (StatusCode, Value)> {
+    body["requests"]
+        .as_array()
+        .ok_or_else(|| error(StatusCode::BAD_REQUEST, "requests must be an array"))
+}
+
+fn batch(state: &mut MockState, index: &str, body: &Value) -> (StatusCode, Value) {
+    let requests = match batch_requests(body) {
+        Ok(requests) => requests,
+        Err(response) => return response,
+    };
+    let mut object_ids = Vec::new();
+    for operation in requests {
+        match apply_operation(state, index, operation) {
+            Ok(object_id) => object_ids.extend(object_id),
+            Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+        }
+    }
+    ok(json!({ "taskID": state.next_task(), "objectIDs": object_ids }))
+}
+
+fn multi_batch(state: &mut MockState, body: &Value) -> (StatusCode, Value) {
+    let requests = match batch_requests(body) {
+        Ok(requests) => requests,
+        Err(response) => return response,
+    };
+    let mut object_ids = Vec::new();
+    let mut task_ids = Map::new();
+    for operation in requests {
+        let Some(index) = operation["indexName"].as_str() else {
+            return error(StatusCode::BAD_REQUEST, "indexName is required");
+        };
+        match apply_operation(state, index, operation) {
+            Ok(object_id) => object_ids.extend(object_id),
+            Err(message) => return error(StatusCode::BAD_REQUEST, &message),
+        }
+        task_ids.insert(index.into(), state.next_task().into());
+    }
+    ok(json!({ "taskID": task_ids, "objectIDs": object_ids }))
+}
+
+/// apply the attributes of a partial update, built-in operations included
+fn partial_update(
+    state: &mut MockState,
+    index: &str,
+    object_id: &str,
+    attributes: &Value,
+    create: bool,
+) -> Result<(), String> {
+    let Value::Object(attributes) = attributes else {
+        return Err("the attributes must be a json object".into());
+    };
+    let index = state.index(index);
+    let mut object = match index.get(object_id) {
+        Some(object) => object.clone(),
+        None if create => Map::from_iter([("objectID".into(), Value::from(object_id))]),
+        None => return Ok(()),
+    };
+    for (attribute, change) in attributes {
+        if attribute == "objectID" {
+            continue;
+        }
+        let (Some(operation), Some(value)) = (change["_operation"].as_str(), change.get("value"))
+        else {
+            object.insert(attribute.clone(), change.clone());
+            continue;
+        };
+        let current = object.get(attribute).cloned().unwrap_or(Value::Null);
+        let number = |value: &Value| value.as_f64().unwrap_or_default();
+        let updated = match operation {
+            "Increment" => Some(json!(number(&current) + number(value))),
+            "Decrement" => Some(json!(number(&current) - number(value))),
+            "IncrementFrom" if number(&current) == number(value) => {
+                Some(json!(number(&current) + 1.0))
+            }
+            "IncrementSet" if number(value) > number(&current) => Some(value.clone()),
+            "IncrementFrom" | "IncrementSet" => None,
+            "Add" | "AddUnique" | "Remove" => {
+                let mut values = match current {
+                    Value::Array(values) => values,
+                    Value::Null => Vec::new(),
+                    value => vec![value],
+                };
+                match operation {
+                    "Remove" => values.retain(|current| current != value),
+                    "AddUnique" if values.contains(value) => {}
+                    _ => values.push(value.clone()),
+                }
+                Some(Value::Array(values))
+            }
+            operation => return Err(format!("unknown operation {operation}")),
+        };
+        if let Some(updated) = updated {
+            object.insert(attribute.clone(), integer_if_whole(updated));
+        }
+    }
+    index.put(object);
+    Ok(())
+}
+
+/// keep integers as integers after arithmetic done with floats
+fn integer_if_whole(value: Value) -> Value {
+    match value.as_f64() {
+        Some(number) if number.fract() == 0.0 && number.abs() < i64::MAX as f64 => {
+            json!(number as i64)
+        }
+        _ => value,
+    }
+}
+
+/// search params decoded from the `params` string of a request body
+struct SearchParams {
+    query: String,
+    /// `filters` and the filter arrays, all of them must match
+    filters: Vec<Filter>,
+    attributes_to_retrieve: Option<Vec<String>>,
+    page: usize,
+    hits_per_page: Option<usize>,
+    offset: Option<usize>,
+    length: Option<usize>,
+    facets: Option<Vec<String>>,
+    click_analytics: bool,
+}
+
+impl SearchParams {
+    fn parse(params: &str) -> Result<Self, String> {
+        let mut search = Self {
+            query: String::new(),
+            filters: Vec::new(),
+            attributes_to_retrieve: None,
+            page: 0,
+            hits_per_page: None,
+            offset: None,
+            length: None,
+            facets: None,
+            click_analytics: false,
+        };
+        for (key, value) in form_urlencoded::parse(params.as_bytes()) {
+            match key.as_ref() {
+                "query" => search.query = value.into_owned(),
+                "filters" if !value.trim().is_empty() => {
+                    search.filters.push(Filter::parse(&value)?)
+                }
+                "facetFilters" | "numericFilters" | "tagFilters" => {
+                    search.filters.push(Filter::parse_array(&key, &value)?)
+                }
+                "attributesToRetrieve" => {
+                    search.attributes_to_retrieve = Some(parse_list(&key, &value)?)
+                }
+                "page" => search.page = parse_number(&key, &value)?,
+                "hitsPerPage" => search.hits_per_page = Some(parse_number(&key, &value)?),
+                "offset" => search.offset = Some(parse_number(&key, &value)?),
+                "length" => search.length = Some(parse_number(&key, &value)?),
+                "facets" => search.facets = Some(parse_list(&key, &value)?),
+                "clickAnalytics" => search.click_analytics = value == "true",
+                _ => {}
+            }
+        }
+        Ok(search)
+    }
+
+    fn matches(&self, object: &Map<String, Value>) -> bool {
+        let object = Value::Object(object.clone());
+        matches_query(&object, &self.query)
+            && self.filters.iter().all(|filters| filters.matches(&object))
+    }
+
+    /// documents of the index matching the query and filters
+    fn hits<'a>(&self, index: Option<&'a MockIndex>) -> Vec<&'a Map<String, Value>> {
+        index
+            .map(|index| {
+                index
+                    .objects
+                    .iter()
+                    .filter(|object| self.matches(object))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// list param, either a json array or comma separated values
+fn parse_list(key: &str, value: &str) -> Result<Vec<String>, String> {
+    if value.trim_start().starts_with('[') {
+        return serde_json::from_str(value)
+            .map_err(|err| format!("{key} must be an array of strings: {err}"));
+    }
+    Ok(value.split(',').map(|item| item.trim().into()).collect())
+}
+
+/// count of hits per value of the requested facets, `*` counts every attribute
+fn facet_counts(facets: &[String], hits: &[&Map<String, Value>]) -> Value {
+    let mut counts: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+    for hit in hits {
+        let attributes: Vec<&str> = match facets.iter().any(|facet| facet == "*") {
+            true => hit.keys().map(String::as_str).collect(),
+            false => facets.iter().map(String::as_str).collect(),
+        };
+        for attribute in attributes {
+            for value in attribute_values(&Value::Object((*hit).clone()), attribute) {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => continue,
+                };
+                *counts
+                    .entry(attribute.into())
+                    .or_default()
+                    .entry(value)
+                    .or_default() += 1;
+            }
+        }
+    }
+    json!(counts)
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{key} must be a positive integer, got {value}"))
+}
+
+fn request_params(body: &Value) -> Result<SearchParams, (StatusCode, Value)> {
+    SearchParams::parse(body["params"].as_str().unwrap_or_default())
+        .map_err(|message| error(StatusCode::BAD_REQUEST, &message))
+}
+
+fn search(state: &MockState, index: &str, body: &Value) -> (StatusCode, Value) {
+    let params = match request_params(body) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+    let hits = params.hits(state.indexes.get(index));
+    let hits_per_page = params.hits_per_page.unwrap_or(20).max(1);
+    // offset and length replace the pages when set
+    let (skip, take) = match params.offset {
+        Some(offset) => (offset, params.length.unwrap_or(hits_per_page)),
+        None => (params.page * hits_per_page, hits_per_page),
+    };
+    let page: Vec<Value> = hits
+        .iter()
+        .skip(skip)
+        .take(take)
+        .map(|object| retrieve(object, params.attributes_to_retrieve.as_deref()))
+        .collect();
+    let mut response = json!({
+        "hits": page,
+        "nbHits": hits.len(),
+        "page": params.page,
+        "nbPages": hits.len().div_ceil(hits_per_page),
+        "hitsPerPage": hits_per_page,
+        "processingTimeMS": 1,
+        "exhaustiveNbHits": true,
+        "query": params.query,
+        "params": body["params"].as_str().unwrap_or_default(),
+    });
+    if let Some(facets) = &params.facets {
+        response["facets"] = facet_counts(facets, &hits);
+    }
+    if params.click_analytics {
+        response["queryID"] = json!(format!("{:032x}", state.last_task));
+    }
+    ok(response)
+}
+
+/// browse pages through every hit, the cursor is the params of the browse with the next page
+fn browse(state: &MockState, index: &str, body: &Value) -> (StatusCode, Value) {
+    let params = body["cursor"]
+        .as_str()
+        .or_else(|| body["params"].as_str())
+        .unwrap_or_default();
+    let search = match request_params(&json!({ "params": params })) {
+        Ok(search) => search,
+        Err(response) => return response,
+    };
+    let hits = search.hits(state.indexes.get(index));
+    let hits_per_page = search.hits_per_page.unwrap_or(1000).max(1);
+    let page: Vec<Value> = hits
+        .iter()
+        .skip(search.page * hits_per_page)
+        .take(hits_per_page)
+        .map(|object| retrieve(object, search.attributes_to_retrieve.as_deref()))
+        .collect();
+    let mut response = json!({
+        "hits": page,
+        "nbHits": hits.len(),
+        "page": search.page,
+        "hitsPerPage": hits_per_page,
+        "processingTimeMS": 1,
+    });
+    if (search.page + 1) * hits_per_page < hits.len() {
+        let cursor = form_urlencoded::parse(params.as_bytes())
+            .filter(|(key, _)| key != "page")
+            .fold(
+                form_urlencoded::Serializer::new(String::new()),
+                |mut cursor, (key, value)| {
+                    cursor.append_pair(&key, &value);
+                    cursor
+                },
+            )
+            .append_pair("page", &(search.page + 1).to_string())
+            .finish();
+        response["cursor"] = Value::String(cursor);
+    }
+    ok(response)
+}
+
+fn delete_by(state: &mut MockState, index: &str, body: &Value) -> (StatusCode, Value) {
+    let params = match request_params(body) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+    if params.filters.is_empty() {
+        return error(StatusCode::BAD_REQUEST, "filters are required");
+    }
+    if let Some(index) = state.indexes.get_mut(index) {
+        index.objects.retain(|object| !params.matches(object));
+    }
+    ok(json!({ "taskID": state.next_task() }))
+}
+
+fn index_operation(state: &mut MockState, source: &str, body: &Value) -> (StatusCode, Value) {
+    let Some(destination) = body["destination"].as_str() else {
+        return error(StatusCode::BAD_REQUEST, "destination is required");
+    };
+    let scope: Vec<&str> = body["scope"]
+        .as_array()
+        .map(|scope| scope.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let (objects, settings) = match state.indexes.get(source) {
+        Some(index) => (index.objects.clone(), index.settings.clone()),
+        None => (Vec::new(), Map::new()),
+    };
+    match body["operation"].as_str() {
+        Some("copy") if scope.is_empty() => {
+            let destination = state.index(destination);
+            destination.objects = objects;
+            destination.settings = settings;
+        }
+        // synonyms and rules are not stored by the mock, only the settings are copied
+        Some("copy") => {
+            if scope.contains(&"settings") {
+                state.index(destination).settings = settings;
+            }
+        }
+        Some("move") => {
+            let moved = state.indexes.remove(source).unwrap_or_default();
+            state.indexes.insert(destination.into(), moved);
+        }
+        _ => return error(StatusCode::BAD_REQUEST, "operation must be copy or move"),
+    }
+    ok(json!({ "taskID": state.next_task() }))
+}
+
+/// document limited to the comma separated attributes, `objectID` is always sent back
+fn retrieve(object: &Map<String, Value>, attributes: Option<&[String]>) -> Value {
+    let Some(attributes) =
+        attributes.filter(|attributes| !attributes.iter().any(|attribute| attribute == "*"))
+    else {
+        return Value::Object(object.clone());
+    };
+    Value::Object(
+        object
+            .iter()
+            .filter(|(name, _)| *name == "objectID" || attributes.contains(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    )
+}
+
+/// every word of the query is found in the text of the document, case insensitive
+fn matches_query(object: &Value, query: &str) -> bool {
+    let mut text = String::new();
+    collect_text(object, &mut text);
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
+
+fn collect_text(value: &Value, text: &mut String) {
+    match value {
+        Value::String(value) => text.push_str(&value.to_lowercase()),
+        Value::Number(value) => text.push_str(&value.to_string()),
+        Value::Array(values) => values.iter().for_each(|value| collect_text(value, text)),
+        Value::Object(values) => values.values().for_each(|value| collect_text(value, text)),
+        Value::Bool(_) | Value::Null => {}
+    }
+    text.push('\n');
+}
+
+/// numeric comparison of a filter such as `price >= 10`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Lower,
+    LowerOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Self::Lower => left < right,
+            Self::LowerOrEqual => left <= right,
+            Self::Equal => left == right,
+            Self::NotEqual => left != right,
+            Self::GreaterOrEqual => left >= right,
+            Self::Greater => left > right,
+        }
+    }
+}
+
+/// parsed Algolia filters
+#[derive(Debug, PartialEq)]
+enum Filter {
+    Or(Vec<Filter>),
+    And(Vec<Filter>),
+    Not(Box<Filter>),
+    /// `attribute:value`, matches when the value or one of the array values is equal
+    Facet {
+        attribute: String,
+        value: String,
+    },
+    /// `attribute > number`
+    Numeric {
+        attribute: String,
+        comparison: Comparison,
+        value: f64,
+    },
+    /// `attribute:low TO high`, bounds included
+    Range {
+        attribute: String,
+        low: f64,
+        high: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Colon,
+    Comparison(Comparison),
+    /// bare or quoted word, quoted words are never keywords
+    Word(String, bool),
+}
+
+fn tokenize(filters: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = filters.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ':' => Token::Colon,
+            // scores only change the ranking, the mock leaves them out
+            '<' if chars.clone().take(6).eq("score=".chars()) => {
+                chars.find(|c| *c == '>');
+                continue;
+            }
+            '<' | '>' | '=' | '!' => {
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                Token::Comparison(match (c, or_equal) {
+                    ('<', false) => Comparison::Lower,
+                    ('<', true) => Comparison::LowerOrEqual,
+                    ('>', false) => Comparison::Greater,
+                    ('>', true) => Comparison::GreaterOrEqual,
+                    ('=', _) => Comparison::Equal,
+                    ('!', true) => Comparison::NotEqual,
+                    _ => return Err("`!` must be followed by `=`".into()),
+                })
+            }
+            '"' | '\'' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => word.extend(chars.next()),
+                        Some(end) if end == c => break,
+                        Some(c) => word.push(c),
+                        None => return Err(format!("unterminated quote in {filters}")),
+                    }
+                }
+                Token::Word(word, true)
+            }
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"():<>=!\"'".contains(*c))
+                {
+                    word.push(c);
+                }
+                Token::Word(word, false)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// recursive descent parser, `AND` binds tighter than `OR`
+struct FilterParser {
+    tokens: std::vec::IntoIter<Token>,
+    peeked: Option<Token>,
+}
+
+impl FilterParser {
+    fn peek(&mut self) -> Option<&Token> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokens.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.peeked.take().or_else(|| self.tokens.next())
+    }
+
+    /// consume the next token if it is the given keyword
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(word, false)) if word == keyword);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn word(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(word, _)) => Ok(word),
+            other => Err(format!("expected a value, found {other:?}")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let word = self.word()?;
+        word.parse()
+            .map_err(|_| format!("expected a number, found {word}"))
+    }
+
+    fn or(&mut self) -> Result<Filter, String> {
+        let mut filters = vec![self.and()?];
+        while self.keyword("OR") {
+            filters.push(self.and()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => Filter::Or(filters),
+        })
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        let mut filters = vec![self.not()?];
+        while self.keyword("AND") {
+            filters.push(self.not()?);
+        }
+        Ok(match filters.len() {
+            1 => filters.remove(0),
+            _ => Filter::And(filters),
+        })
+    }
+
+    fn not(&mut self) -> Result<Filter, String> {
+        if self.keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Filter, String> {
+        if self.peek() == Some(&Token::Open) {
+            self.next();
+            let filter = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(filter),
+                other => Err(format!("expected `)`, found {other:?}")),
+            };
+        }
+        let attribute = self.word()?;
+        match self.next() {
+            Some(Token::Colon) => match self.peek() {
+                // `price:>10` is read as `price > 10`
+                Some(Token::Comparison(comparison)) => {
+                    let comparison = *comparison;
+                    self.next();
+                    Ok(Filter::Numeric {
+                        attribute,
+                        comparison,
+                        value: self.number()?,
+                    })
+                }
+                _ => {
+                    let value = self.word()?;
+                    if !self.keyword("TO") {
+                        return Ok(Filter::Facet { attribute, value });
+                    }
+                    let low = value
+                        .parse()
+                        .map_err(|_| format!("expected a number, found {value}"))?;
+                    Ok(Filter::Range {
+                        attribute,
+                        low,
+                        high: self.number()?,
+                    })
+                }
+            },
+            Some(Token::Comparison(comparison)) => Ok(Filter::Numeric {
+                attribute,
+                comparison,
+                value: self.number()?,
+            }),
+            other => Err(format!(
+                "expected `:` or a comparison after {attribute}, found {other:?}"
+            )),
+        }
+    }
+}
+
+impl Filter {
+    fn parse(filters: &str) -> Result<Self, String> {
+        let mut parser = FilterParser {
+            tokens: tokenize(filters)?.into_iter(),
+            peeked: None,
+        };
+        let filter = parser.or()?;
+        match parser.next() {
+            None => Ok(filter),
+            Some(token) => Err(format!("unexpected {token:?} in filters {filters}")),
+        }
+    }
+
+    /// filters of a `facetFilters`, `numericFilters` or `tagFilters` json array, the array is an
+    /// AND and its nested arrays are OR groups
+    fn parse_array(param: &str, filters: &str) -> Result<Self, String> {
+        let filters: Value = serde_json::from_str(filters)
+            .map_err(|err| format!("{param} must be a json array: {err}"))?;
+        let element = |filter: &Value| {
+            let filter = filter
+                .as_str()
+                .ok_or_else(|| format!("{param} must only hold strings, found {filter}"))?;
+            Self::parse_array_element(param, filter)
+        };
+        let filters = match filters {
+            Value::Array(filters) => filters,
+            filter => vec![filter],
+        };
+        filters
+            .iter()
+            .map(|filter| match filter {
+                Value::Array(group) => group
+                    .iter()
+                    .map(element)
+                    .collect::<Result<_, _>>()
+                    .map(Self::Or),
+                filter => element(filter),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::And)
+    }
+
+    fn parse_array_element(param: &str, filter: &str) -> Result<Self, String> {
+        if param == "numericFilters" {
+            return Self::parse(filter);
+        }
+        let (attribute, value) = match param {
+            "tagFilters" => ("_tags", filter),
+            _ => filter
+                .split_once(':')
+                .ok_or_else(|| format!("expected `attribute:value` in {param}, found {filter}"))?,
+        };
+        // `-value` negates the filter and `\-value` is a value starting with `-`
+        let (value, negated) = match value.strip_prefix('-') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let value = value.strip_prefix('\\').unwrap_or(value);
+        let facet = Self::Facet {
+            attribute: attribute.into(),
+            value: value.into(),
+        };
+        Ok(match negated {
+            true => Self::Not(Box::new(facet)),
+            false => facet,
+        })
+    }
+
+    fn matches(&self, object: &Value) -> bool {
+        match self {
+            Self::Or(filters) => filters.iter().any(|filter| filter.matches(object)),
+            Self::And(filters) => filters.iter().all(|filter| filter.matches(object)),
+            Self::Not(filter) => !filter.matches(object),
+            Self::Facet { attribute, value } => attribute_values(object, attribute)
+                .iter()
+                .any(|candidate| facet_equals(candidate, value)),
+            Self::Numeric {
+                attribute,
+                comparison,
+                value,
+            } => attribute_values(object, attribute)
+                .iter()
+                .filter_map(|candidate| candidate.as_f64())
+                .any(|candidate| comparison.holds(candidate, *value)),
+            Self::Range {
+                attribute,
+                low,
+                high,
+            } => attribute_values(object, attribute)
+                .iter()
+                .filter_map(|candidate| candidate.as_f64())
+                .any(|candidate| (*low..=*high).contains(&candidate)),
+        }
+    }
+}
+
+/// values of a possibly nested attribute such as `author.name`, arrays are flattened
+fn attribute_values<'a>(object: &'a Value, attribute: &str) -> Vec<&'a Value> {
+    let mut values = vec![object];
+    for key in attribute.split('.') {
+        values = values
+            .into_iter()
+            .flat_map(|value| match value {
+                Value::Array(items) => items.iter().filter_map(|item| item.get(key)).collect(),
+                value => value.get(key).into_iter().collect::<Vec<_>>(),
+            })
+            .collect();
+    }
+    values
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        })
+        .collect()
+}
+
+fn facet_equals(candidate: &Value, value: &str) -> bool {
+    match candidate {
+        Value::String(candidate) => candidate.eq_ignore_ascii_case(value),
+        Value::Bool(candidate) => candidate.to_string() == value,
+        Value::Number(candidate) => value
+            .parse::<f64>()
+            .is_ok_and(|value| candidate.as_f64() == Some(value)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn facet(attribute: &str, value: &str) -> Filter {
+        Filter::Facet {
+            attribute: attribute.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn test_filter_parse() {
+        assert_eq!(
+            Filter::parse("brand:apple AND color:red OR NOT \"on sale\":true").unwrap(),
+            Filter::Or(vec![
+                Filter::And(vec![facet("brand", "apple"), facet("color", "red")]),
+                Filter::Not(Box::new(facet("on sale", "true"))),
+            ])
+        );
+        assert_eq!(
+            Filter::parse("(price >= 10 OR price:5 TO 7) AND name:'Last of us'").unwrap(),
+            Filter::And(vec![
+                Filter::Or(vec![
+                    Filter::Numeric {
+                        attribute: "price".into(),
+                        comparison: Comparison::GreaterOrEqual,
+                        value: 10.0,
+                    },
+                    Filter::Range {
+                        attribute: "price".into(),
+                        low: 5.0,
+                        high: 7.0,
+                    },
+                ]),
+                facet("name", "Last of us"),
+            ])
+        );
+        assert!(Filter::parse("(brand:apple").is_err());
+        assert!(Filter::parse("brand apple").is_err());
+        assert!(Filter::parse("price > cheap").is_err());
+    }
+
+    #[test]
+    fn test_filter_array_parse() {
+        assert_eq!(
+            Filter::parse_array(
+                "facetFilters",
+                r#"[["genre:rpg", "genre:-\\-1"], "platform:-pc"]"#
+            )
+            .unwrap(),
+            Filter::And(vec![
+                Filter::Or(vec![
+                    facet("genre", "rpg"),
+                    Filter::Not(Box::new(facet("genre", "-1"))),
+                ]),
+                Filter::Not(Box::new(facet("platform", "pc"))),
+            ])
+        );
+        assert_eq!(
+            Filter::parse_array("tagFilters", r#"["beta", ["-alpha"]]"#).unwrap(),
+            Filter::And(vec![
+                facet("_tags", "beta"),
+                Filter::Or(vec![Filter::Not(Box::new(facet("_tags", "alpha")))]),
+            ])
+        );
+        assert_eq!(
+            Filter::parse_array("numericFilters", r#""price:5 TO 7""#).unwrap(),
+            Filter::And(vec![Filter::Range {
+                attribute: "price".into(),
+                low: 5.0,
+                high: 7.0,
+            }])
+        );
+        assert_eq!(
+            Filter::parse("brand:apple<score=2>").unwrap(),
+            facet("brand", "apple")
+        );
+        assert!(Filter::parse_array("facetFilters", "genre:rpg").is_err());
+        assert!(Filter::parse_array("facetFilters", r#"["rpg"]"#).is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let game = json!({
+            "name": "Halo",
+            "price": 20,
+            "platforms": ["xbox", "pc"],
+            "studio": { "name": "Bungie" },
+            "multiplayer": true
+        });
+        let matches = |filters: &str| Filter::parse(filters).unwrap().matches(&game);
+        assert!(matches("platforms:PC AND price:>10"));
+        assert!(matches("studio.name:bungie AND multiplayer:true"));
+        assert!(matches("price:10 TO 20 AND NOT platforms:playstation"));
+        assert!(matches("price = 20 OR platforms:switch"));
+        assert!(!matches("price != 20"));
+        assert!(!matches(
+            "(platforms:switch OR platforms:playstation) AND price < 30"
+        ));
+        assert!(!matches("missing:value"));
+    }
+
+    #[test]
+    fn test_query_matches_substrings() {
+        let game = json!({ "name": "The Last of Us", "tags": ["Survival"], "year": 2013 });
+        assert!(matches_query(&game, ""));
+        assert!(matches_query(&game, "last surviv"));
+        assert!(matches_query(&game, "2013"));
+        assert!(!matches_query(&game, "last halo"));
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /1/indexes/Games%20Old/batch?x=a%2Cb HTTP/1.1\r\n\
+                   Content-Type: application/json\r\nContent-Length: 2\r\n\r\n{}";
+        let request = read_request(&mut raw.as_bytes()).unwrap().unwrap().unwrap();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.path, "/1/indexes/Games%20Old/batch");
+        assert_eq!(request.query, vec![("x".into(), "a,b".into())]);
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.body, json!({}));
+        assert!(read_request(&mut "".as_bytes()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,439 @@
+//! Http transport
+//! every operation is described once as a request, the client picks the host, attaches the
+//! credentials and hands the [HttpRequest] to an [HttpRequester] which sends it
+//! requests are sent with reqwest by default, any http client can be plugged in with
+//! [set_http_requester](crate::client_builder::ClientBuilder::set_http_requester), and
+//! [InMemoryRequester] answers with canned responses for unit tests
+use futures::future::BoxFuture;
+#[cfg(feature = "reqwest")]
+use futures::FutureExt;
+use http::{
+    header::HeaderMap,
+    Method,
+    StatusCode,
+};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// request ready to be sent to a host
+/// the credentials headers are marked sensitive and are hidden from the `Debug` output
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    /// full url, host included
+    pub url: String,
+    pub headers: HeaderMap,
+    /// json body
+    pub body: Option<Vec<u8>>,
+    /// time to get the whole response, from sending the request to reading the body
+    pub timeout: Duration,
+}
+
+/// status and whole body of a response
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+}
+
+/// why a request got no response, decides whether the request is sent to another host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpErrorKind {
+    /// the connection could not be established, the host never saw the request
+    Connect,
+    /// the response did not come back in time
+    Timeout,
+    /// the request could not be built, no host would take it
+    InvalidRequest,
+    /// the connection failed while the request or the response was in flight
+    Other,
+}
+
+/// error of an [HttpRequester]
+pub struct HttpError {
+    kind: HttpErrorKind,
+    source: Box<dyn Error + Send + Sync + 'static>,
+}
+
+impl HttpError {
+    pub fn new(kind: HttpErrorKind, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    pub fn kind(&self) -> HttpErrorKind {
+        self.kind
+    }
+
+    pub(crate) fn into_source(self) -> Box<dyn Error + Send + Sync + 'static> {
+        self.source
+    }
+}
+
+impl fmt::Debug for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpError")
+            .field("kind", &self.kind)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl Error for HttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// sends a request to a single host, retries are handled by the client
+/// # Examples
+/// ```
+/// use futures::{
+///     future::BoxFuture,
+///     FutureExt,
+/// };
+/// use EasyAlgolia::transport::{
+///     HttpError,
+///     HttpRequest,
+///     HttpRequester,
+///     HttpResponse,
+/// };
+/// struct MyRequester;
+/// impl HttpRequester for MyRequester {
+///     fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, HttpError>> {
+///         async move {
+///             // send `request` with the http client of your choice
+///             Ok(HttpResponse::new(http::StatusCode::OK, "{}"))
+///         }
+///         .boxed()
+///     }
+/// }
+/// ```
+pub trait HttpRequester: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, HttpError>>;
+
+    /// used by the blocking methods of the client, defaults to driving
+    /// [send](HttpRequester::send) with `futures::executor::block_on`, requesters needing an
+    /// async runtime must override it
+    /// declared whatever the features, only the blocking methods of the client need the
+    /// `blocking` feature
+    fn send_blocking(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        futures::executor::block_on(self.send(request))
+    }
+}
+
+/// connection settings of the reqwest clients
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub(crate) struct HttpSettings {
+    pub(crate) connect_timeout: Duration,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: usize,
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(2),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            pool_max_idle_per_host: usize::MAX,
+        }
+    }
+}
+
+/// apply the settings to a `reqwest::ClientBuilder` or a `reqwest::blocking::ClientBuilder`
+#[cfg(feature = "reqwest")]
+macro_rules! configure {
+    ($builder:expr, $settings:expr) => {
+        $builder
+            .connect_timeout($settings.connect_timeout)
+            .pool_idle_timeout($settings.pool_idle_timeout)
+            .pool_max_idle_per_host($settings.pool_max_idle_per_host)
+            .build()
+    };
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for HttpError {
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if err.is_builder() {
+            HttpErrorKind::InvalidRequest
+        } else if err.is_connect() {
+            HttpErrorKind::Connect
+        } else if err.is_timeout() {
+            HttpErrorKind::Timeout
+        } else {
+            HttpErrorKind::Other
+        };
+        Self::new(kind, err)
+    }
+}
+
+/// requester backed by reqwest, used by default
+#[cfg(feature = "reqwest")]
+pub(crate) struct ReqwestRequester {
+    client: reqwest::Client,
+    /// built on the first blocking request, async only users never start its thread
+    #[cfg(feature = "blocking")]
+    blocking: std::sync::OnceLock<reqwest::blocking::Client>,
+    #[cfg(feature = "blocking")]
+    settings: HttpSettings,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestRequester {
+    pub(crate) fn new(settings: HttpSettings) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: configure!(reqwest::Client::builder(), settings)?,
+            #[cfg(feature = "blocking")]
+            blocking: std::sync::OnceLock::new(),
+            #[cfg(feature = "blocking")]
+            settings,
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    fn blocking_client(&self) -> Result<&reqwest::blocking::Client, reqwest::Error> {
+        if let Some(client) = self.blocking.get() {
+            return Ok(client);
+        }
+        let client = configure!(reqwest::blocking::Client::builder(), self.settings)?;
+        Ok(self.blocking.get_or_init(|| client))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpRequester for ReqwestRequester {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, HttpError>> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .timeout(request.timeout);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        async move {
+            let response = builder.send().await?;
+            let status = response.status();
+            let body = response.text().await?;
+            Ok(HttpResponse { status, body })
+        }
+        .boxed()
+    }
+
+    #[cfg(feature = "blocking")]
+    fn send_blocking(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let mut builder = self
+            .blocking_client()?
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .timeout(request.timeout);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        let response = builder.send()?;
+        let status = response.status();
+        let body = response.text()?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+#[derive(Default)]
+struct InMemoryState {
+    responses: VecDeque<Result<HttpResponse, HttpError>>,
+    requests: Vec<HttpRequest>,
+}
+
+/// requester for unit tests, records every request and answers with canned responses in the
+/// order they were pushed
+/// clones share the same state, keep one to inspect the requests once the client is built
+/// # Examples
+/// ```
+/// use http::StatusCode;
+/// use EasyAlgolia::{
+///     client_builder::ClientBuilder,
+///     transport::{
+///         HttpResponse,
+///         InMemoryRequester,
+///     },
+/// };
+/// let requester = InMemoryRequester::new();
+/// requester.push_response(HttpResponse::new(StatusCode::OK, r#"{"items":[]}"#));
+/// let client = ClientBuilder::new()
+///     .set_application_id("123")
+///     .set_api_key("123")
+///     .set_http_requester(requester.clone())
+///     .build()
+///     .unwrap();
+/// let indexes = futures::executor::block_on(client.list_indexes_async()).unwrap();
+/// assert!(indexes.is_empty());
+/// assert_eq!(
+///     requester.requests()[0].url,
+///     "https://123-dsn.algolia.net/1/indexes"
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct InMemoryRequester {
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+impl InMemoryRequester {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// response given to the next request without one
+    pub fn push_response(&self, response: HttpResponse) {
+        self.state().responses.push_back(Ok(response));
+    }
+
+    /// error given to the next request without a response
+    pub fn push_error(&self, error: HttpError) {
+        self.state().responses.push_back(Err(error));
+    }
+
+    /// every request received so far, in order
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, InMemoryState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn respond(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let mut state = self.state();
+        state.requests.push(request);
+        state.responses.pop_front().unwrap_or_else(|| {
+            Err(HttpError::new(
+                HttpErrorKind::Other,
+                "no response left in the in memory requester",
+            ))
+        })
+    }
+}
+
+impl HttpRequester for InMemoryRequester {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, HttpError>> {
+        let response = self.respond(request);
+        Box::pin(async move { response })
+    }
+
+    fn send_blocking(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        self.respond(request)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        client_builder::ClientBuilder,
+        error::ErrorKind,
+    };
+
+    fn client(requester: &InMemoryRequester) -> crate::Client {
+        ClientBuilder::new()
+            .set_application_id("APP")
+            .set_api_key("secret")
+            .set_http_requester(requester.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_in_memory_requester_records_requests() {
+        let requester = InMemoryRequester::new();
+        requester.push_response(HttpResponse::new(
+            StatusCode::OK,
+            r#"{"taskID":3,"objectID":"halo"}"#,
+        ));
+        let response = futures::executor::block_on(client(&requester).partial_update_async(
+            &"Games".into(),
+            "halo".into(),
+            crate::partial_update::PartialUpdate::new().set("name", "Halo"),
+        ))
+        .unwrap();
+        assert_eq!(response.task_id.get(), 3);
+
+        let request = &requester.requests()[0];
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(
+            request.url,
+            "https://APP.algolia.net/1/indexes/Games/halo/partial?createIfNotExists=true"
+        );
+        assert_eq!(request.headers["X-Algolia-API-Key"], "secret");
+        assert!(request.headers["X-Algolia-API-Key"].is_sensitive());
+        assert_eq!(
+            request.body.as_deref(),
+            Some(br#"{"name":"Halo"}"#.as_slice())
+        );
+        assert!(!format!("{request:?}").contains("secret"));
+    }
+
+    #[test]
+    fn test_in_memory_requester_errors_fail_over() {
+        let requester = InMemoryRequester::new();
+        requester.push_error(HttpError::new(HttpErrorKind::Timeout, "timed out"));
+        requester.push_response(HttpResponse::new(StatusCode::OK, r#"{"items":[]}"#));
+        let client = client(&requester);
+        assert!(futures::executor::block_on(client.list_indexes_async())
+            .unwrap()
+            .is_empty());
+        let urls: Vec<String> = requester.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://APP-dsn.algolia.net/1/indexes",
+                "https://APP-1.algolianet.com/1/indexes",
+            ]
+        );
+
+        // without any response left the requester fails like a broken connection
+        let err = futures::executor::block_on(client.list_indexes_async()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Transport);
+    }
+
+    #[test]
+    fn test_send_blocking_without_blocking_feature() {
+        let requester = InMemoryRequester::new();
+        requester.push_response(HttpResponse::new(StatusCode::OK, "{}"));
+        let request = HttpRequest {
+            method: Method::GET,
+            url: "https://APP.algolia.net/1/indexes".into(),
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: Duration::from_secs(1),
+        };
+        let response = requester.send_blocking(request).unwrap();
+        assert_eq!(response.body, "{}");
+        assert_eq!(requester.requests().len(), 1);
+    }
+}
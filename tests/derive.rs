@@ -0,0 +1,219 @@
+//! object ids generated by #[derive(AlgoliaObject)]
+#[cfg(test)]
+mod test {
+    use serde_json::Value;
+    use std::fmt::Display;
+    use EasyAlgolia::{
+        algoliaobject::{
+            AlgoliaObject,
+            AttributeName,
+            Order,
+            QuerySort,
+            SearchQuery,
+        },
+        batch::BatchOperation,
+        filter::Filter,
+        AlgoliaIndexSetting,
+    };
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    struct Game {
+        name: String,
+        #[algolia(object_id)]
+        slug: String,
+    }
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    struct Score(#[algolia(object_id)] u64, f32);
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    #[algolia(object_id = r#"format!("{}-{}", self.platform, self.name)"#)]
+    struct Release {
+        name: String,
+        platform: String,
+    }
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    struct Draft {
+        name: String,
+    }
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    struct Tagged<T: serde::Serialize + Display> {
+        #[algolia(object_id)]
+        id: T,
+    }
+
+    #[derive(serde::Serialize, AlgoliaObject)]
+    #[algolia(object_id = "self.id()")]
+    enum Media {
+        Game { slug: String },
+        Movie(u32),
+    }
+
+    impl Media {
+        fn id(&self) -> String {
+            match self {
+                Self::Game { slug } => format!("game-{slug}"),
+                Self::Movie(id) => format!("movie-{id}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_object_id() {
+        let game = Game {
+            name: "Halo".into(),
+            slug: "halo".into(),
+        };
+        assert_eq!(game.get_object_id(), "halo");
+        assert_eq!(Score(42, 0.5).get_object_id(), "42");
+        assert_eq!(Tagged { id: 'x' }.get_object_id(), "x");
+    }
+
+    #[test]
+    fn test_expression_object_id() {
+        let release = Release {
+            name: "halo".into(),
+            platform: "xbox".into(),
+        };
+        assert_eq!(release.get_object_id(), "xbox-halo");
+        let game = Media::Game {
+            slug: "halo".into(),
+        };
+        assert_eq!(game.get_object_id(), "game-halo");
+        assert_eq!(Media::Movie(7).get_object_id(), "movie-7");
+    }
+
+    #[test]
+    fn test_without_object_id_algolia_generates_it() {
+        let draft = Draft {
+            name: "Halo".into(),
+        };
+        assert_eq!(draft.get_object_id(), "");
+        assert!(matches!(
+            BatchOperation::upsert(draft),
+            BatchOperation::AddObject(_)
+        ));
+    }
+
+    #[derive(serde::Serialize, Default)]
+    struct Studio {
+        studio_name: String,
+    }
+
+    #[derive(serde::Serialize, Default, AlgoliaObject)]
+    #[serde(rename_all = "camelCase")]
+    struct Product {
+        #[algolia(object_id)]
+        sku: String,
+        release_year: u32,
+        #[serde(rename = "Price")]
+        unit_price: f32,
+        #[serde(rename(serialize = "kind", deserialize = "type"))]
+        r#type: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        discount: Option<f32>,
+        #[serde(skip)]
+        cached: bool,
+        #[serde(flatten)]
+        studio: Studio,
+        #[algolia(skip)]
+        internal_notes: String,
+    }
+
+    /// serialized by hand, the attribute names can not be read from serde
+    #[derive(AlgoliaObject)]
+    struct Legacy {
+        #[algolia(rename = "legacy_id")]
+        id: u32,
+        #[algolia(rename = "DISPLAY-NAME")]
+        display_name: String,
+    }
+
+    impl serde::Serialize for Legacy {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serde_json::json!({ "legacy_id": self.id, "DISPLAY-NAME": self.display_name })
+                .serialize(serializer)
+        }
+    }
+
+    #[test]
+    fn test_attribute_names_follow_serde() {
+        assert_eq!(Product::SKU, "sku");
+        assert_eq!(Product::RELEASE_YEAR, "releaseYear");
+        assert_eq!(Product::UNIT_PRICE, "Price");
+        assert_eq!(Product::TYPE, "kind");
+        assert_eq!(Product::DISCOUNT, "discount");
+        assert_eq!(
+            ProductField::ALL,
+            &[
+                ProductField::Sku,
+                ProductField::ReleaseYear,
+                ProductField::UnitPrice,
+                ProductField::Type,
+                ProductField::Discount,
+            ]
+        );
+        // every generated name is an attribute of the serialized document
+        let product = Product {
+            discount: Some(0.1),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_value(&product).unwrap();
+        for field in ProductField::ALL {
+            assert!(serialized.get(field.attribute_name()).is_some(), "{field}");
+        }
+        assert_eq!(
+            serialized.as_object().unwrap().len(),
+            ProductField::ALL.len() + 2,
+            "only the flattened studio name and the skipped notes have no field"
+        );
+        assert!(!product.cached);
+    }
+
+    #[test]
+    fn test_algolia_rename() {
+        assert_eq!(Legacy::ID, "legacy_id");
+        assert_eq!(Legacy::DISPLAY_NAME, "DISPLAY-NAME");
+        let legacy = Legacy {
+            id: 1,
+            display_name: "Halo".into(),
+        };
+        let serialized = serde_json::to_value(&legacy).unwrap();
+        for field in LegacyField::ALL {
+            assert!(serialized.get(field.to_string()).is_some());
+        }
+        assert_eq!(legacy.get_object_id(), "");
+    }
+
+    #[test]
+    fn test_attribute_names_in_queries_and_settings() {
+        let query = SearchQuery::new()
+            .query("phone")
+            .filter(Filter::gt(ProductField::UnitPrice, 10));
+        assert_eq!(
+            query.to_params_string().unwrap(),
+            "filters=Price+%3E+10&query=phone"
+        );
+        // a sort names the attribute like the filters
+        assert_eq!(
+            ProductField::ReleaseYear.desc().get_query(),
+            "releaseYear:desc"
+        );
+        let settings = AlgoliaIndexSetting {
+            searchable_attributes: Some(vec![Product::SKU.into(), Product::TYPE.into()]),
+            custom_ranking: Some(vec![format!("desc({})", ProductField::ReleaseYear)]),
+            ..Default::default()
+        };
+        let settings: Value = serde_json::to_value(settings).unwrap();
+        assert_eq!(
+            settings["searchable_attributes"],
+            serde_json::json!(["sku", "kind"])
+        );
+        assert_eq!(GameField::ALL, &[GameField::Name, GameField::Slug]);
+    }
+}
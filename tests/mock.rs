@@ -0,0 +1,291 @@
+//! end to end tests of the client against the mock Algolia server
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::{
+        json,
+        Value,
+    };
+    use EasyAlgolia::{
+        algoliaobject::SearchQuery,
+        batch::BatchOperation,
+        browse::BrowseParams,
+        error::ErrorKind,
+        filter::Filter,
+        partial_update::PartialUpdate,
+        response::SearchResponse,
+        testing::{
+            MockAlgolia,
+            MOCK_APPLICATION_ID,
+        },
+        AlgoliaIndexSetting,
+        Index,
+        ObjectId,
+    };
+
+    fn games() -> Vec<BatchOperation<Value>> {
+        [
+            json!({ "objectID": "halo", "name": "Halo", "platform": "xbox", "price": 20 }),
+            json!({ "objectID": "tlou", "name": "The Last of Us", "platform": "playstation", "price": 40 }),
+            json!({ "objectID": "hades", "name": "Hades", "platform": ["pc", "switch"], "price": 25 }),
+        ]
+        .into_iter()
+        .map(BatchOperation::upsert)
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn test_mock_objects() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+
+        let written = client
+            .put_document_async(&index, &json!({ "objectID": "halo", "name": "Halo" }))
+            .await
+            .unwrap();
+        assert_eq!(written.object_id.as_deref(), Some("halo"));
+        let generated = client
+            .put_document_async(&index, &json!({ "name": "Hades" }))
+            .await
+            .unwrap()
+            .object_id
+            .unwrap();
+
+        let halo: Option<Value> = client
+            .get_object_async(&index, &"halo".into(), Some(&["name"]))
+            .await
+            .unwrap();
+        assert_eq!(halo, Some(json!({ "objectID": "halo", "name": "Halo" })));
+        let objects: Vec<Option<Value>> = client
+            .get_objects_async([
+                (index.clone(), ObjectId::from(generated.as_str())),
+                (index.clone(), ObjectId::from("missing")),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(objects[0].as_ref().unwrap()["name"], "Hades");
+        assert!(objects[1].is_none());
+
+        let update = PartialUpdate::new()
+            .increment("views", 2)
+            .add_unique("tags", "shooter");
+        client
+            .partial_update_async(&index, "halo".into(), update)
+            .await
+            .unwrap();
+        client
+            .delete_document_async(&index, ObjectId::from(generated.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(
+            algolia.objects("Games"),
+            vec![json!({ "objectID": "halo", "name": "Halo", "views": 2, "tags": ["shooter"] })]
+        );
+        let missing: Option<Value> = client
+            .get_object_async(&index, &generated.as_str().into(), None)
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_batch_and_search() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        let response = client.batch_async(&index, games()).await.unwrap();
+        assert_eq!(response.object_ids, vec!["halo", "tlou", "hades"]);
+
+        let search = SearchQuery::new()
+            .query("ha")
+            .filter("price:>10")
+            .and_filter("NOT platform:playstation");
+        let found: SearchResponse<Value> = client.search_async(&index, search).await.unwrap();
+        assert_eq!(found.nb_hits, 2);
+        assert_eq!(found.hits[0]["objectID"], "halo");
+        assert_eq!(found.hits[1]["objectID"], "hades");
+        let found: SearchResponse<Value> = client
+            .search_async(&index, SearchQuery::new().filter("platform:pc"))
+            .await
+            .unwrap();
+        assert_eq!(found.hits.len(), 1);
+
+        // one document per page, the mock hands back a cursor until the last one
+        let params = BrowseParams::new()
+            .filters("price:20 TO 30")
+            .hits_per_page(1);
+        let browsed: Vec<Value> = client
+            .browse_async(&index, params)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(browsed.len(), 2);
+
+        client
+            .delete_by_async(&index, SearchQuery::new().filter("price < 30"))
+            .await
+            .unwrap();
+        assert_eq!(algolia.objects("Games").len(), 1);
+        assert_eq!(algolia.objects("Games")[0]["objectID"], "tlou");
+    }
+
+    #[tokio::test]
+    async fn test_mock_delete_by_filter_arrays() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        client.batch_async(&index, games()).await.unwrap();
+
+        // the facet filters narrow the filter, hades is cheap enough but not on xbox
+        let filters = SearchQuery::new()
+            .filter("price < 30")
+            .facet_filters(Filter::eq("platform", "xbox"));
+        client.delete_by_async(&index, filters).await.unwrap();
+        let mut ids: Vec<Value> = algolia
+            .objects("Games")
+            .iter()
+            .map(|object| object["objectID"].clone())
+            .collect();
+        ids.sort_by_key(|id| id.to_string());
+        assert_eq!(ids, ["hades", "tlou"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_typed_filters() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        client.batch_async(&index, games()).await.unwrap();
+
+        // numeric filters can not share an OR group with facet filters
+        let filter = Filter::eq("name", "The Last of Us")
+            .or(Filter::eq("platform", "xbox"))
+            .and(Filter::range("price", 15, 45));
+        let browsed: Vec<Value> = client
+            .browse_async(&index, BrowseParams::new().filters(filter))
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<&Value> = browsed.iter().map(|hit| &hit["objectID"]).collect();
+        assert_eq!(ids, ["halo", "tlou"]);
+
+        let search = SearchQuery::new()
+            .filter(Filter::ne("platform", "pc"))
+            .and_filter(!Filter::gt("price", 30));
+        let found: SearchResponse<Value> = client.search_async(&index, search).await.unwrap();
+        assert_eq!(found.hits.len(), 1);
+        assert_eq!(found.hits[0]["objectID"], "halo");
+
+        let search = SearchQuery::new()
+            .facet_filters(Filter::eq("platform", "pc").or(Filter::eq("platform", "xbox")))
+            .numeric_filters(Filter::lt("price", 25))
+            .optional_filters(Filter::eq("name", "Hades").score(2));
+        let found: SearchResponse<Value> = client.search_async(&index, search).await.unwrap();
+        assert_eq!(found.hits.len(), 1);
+        assert_eq!(found.hits[0]["objectID"], "halo");
+
+        // refused before reaching Algolia
+        let requests = algolia.requests().len();
+        let search = SearchQuery::new().facet_filters(Filter::gt("price", 10));
+        let refused = client.search_async::<Value>(&index, search).await;
+        assert_eq!(refused.unwrap_err().kind(), ErrorKind::InvalidRequest);
+        assert_eq!(algolia.requests().len(), requests);
+    }
+
+    #[tokio::test]
+    async fn test_mock_search_params() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        client.batch_async(&index, games()).await.unwrap();
+
+        let search = SearchQuery::new()
+            .hits_per_page(1)
+            .page(1)
+            .attributes_to_retrieve(["name"])
+            .facets(["platform"])
+            .click_analytics(true);
+        let found: SearchResponse<Value> = client.search_async(&index, search).await.unwrap();
+        assert_eq!(
+            found.hits,
+            [json!({ "objectID": "tlou", "name": "The Last of Us" })]
+        );
+        assert_eq!((found.page, found.nb_pages, found.nb_hits), (1, 3, 3));
+        assert_eq!(found.facets["platform"]["pc"], 1);
+        assert_eq!(found.facets["platform"].len(), 4);
+        assert!(found.query_id.is_some());
+
+        let search = SearchQuery::new().offset(1).length(2);
+        let found: SearchResponse<Value> = client.search_async(&index, search).await.unwrap();
+        let ids: Vec<&Value> = found.hits.iter().map(|hit| &hit["objectID"]).collect();
+        assert_eq!(ids, ["tlou", "hades"]);
+        assert!(found.facets.is_empty());
+        assert_eq!(found.query_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_mock_settings_and_tasks() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        let settings = AlgoliaIndexSetting {
+            hits_per_page: 50,
+            searchable_attributes: Some(vec!["name".into()]),
+            ..Default::default()
+        };
+        let written = client
+            .update_index_setting::<Value>(&index, settings)
+            .await
+            .unwrap();
+        client
+            .wait_task_async(&index, written.task_id)
+            .await
+            .unwrap();
+        let settings = client.get_index_setting::<Value>(&index).await.unwrap();
+        assert_eq!(settings.hits_per_page, 50);
+        assert_eq!(settings.searchable_attributes.unwrap(), vec!["name"]);
+
+        let response = client.batch_async(&index, games()).await.unwrap();
+        for task_id in response.task_ids {
+            client.wait_task_async(&index, task_id).await.unwrap();
+        }
+        let indexes = client.list_indexes_async().await.unwrap();
+        assert_eq!(indexes[0].name, "Games");
+        assert_eq!(indexes[0].entries, 3);
+        assert!(client
+            .get_task_status_async(&index, 999.into())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_records_requests() {
+        let algolia = MockAlgolia::start().unwrap();
+        let client = algolia.client();
+        let index: Index = "Games".into();
+        client.batch_async(&index, games()).await.unwrap();
+
+        let requests = algolia.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, http::Method::POST);
+        assert_eq!(requests[0].path, "/1/indexes/Games/batch");
+        assert_eq!(
+            requests[0].headers["x-algolia-application-id"],
+            MOCK_APPLICATION_ID
+        );
+        assert_eq!(requests[0].body["requests"][0]["action"], "addObject");
+        assert_eq!(requests[0].body["requests"][2]["body"]["name"], "Hades");
+
+        algolia.clear_requests();
+        let _: Option<Value> = client
+            .get_object_async(&index, &"halo".into(), Some(&["name", "price"]))
+            .await
+            .unwrap();
+        let requests = algolia.requests();
+        assert_eq!(
+            requests[0].query,
+            vec![("attributesToRetrieve".to_string(), "name,price".to_string())]
+        );
+    }
+}
@@ -1,9 +1,19 @@
 #[cfg(test)]
 mod test {
-    use EasyAlgolia::client_builder::ClientBuilder;
+    use EasyAlgolia::{
+        client_builder::{
+            ClientBuilder,
+            AGENT,
+        },
+        transport::{
+            HttpResponse,
+            InMemoryRequester,
+        },
+    };
     // Import the ClientBuilder type from the module where it's defined
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_client_builder() {
         let app_id = "123";
         let _ = ClientBuilder::new()
@@ -12,4 +22,149 @@ mod test {
         // Add assertions or other test logic here
         assert!(true)
     }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_client_builder_builds_with_reqwest() {
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .build();
+        assert!(client.is_ok())
+    }
+
+    #[test]
+    fn test_client_builder_rejects_zero_batch_size() {
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .set_batch_size(0)
+            .build();
+        assert!(client.is_err())
+    }
+
+    #[test]
+    fn test_client_builder_rejects_invalid_header() {
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .add_header("X-Tenant", "line\nbreak")
+            .build();
+        assert!(client.is_err())
+    }
+
+    #[test]
+    fn test_client_builder_rejects_empty_hosts() {
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .hosts(vec![])
+            .build();
+        assert!(client.is_err())
+    }
+
+    #[test]
+    fn test_client_builder_rejects_hosts_with_retry_strategy() {
+        use EasyAlgolia::retry::{
+            DefaultRetryStrategy,
+            Host,
+        };
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .set_http_requester(InMemoryRequester::new())
+            .set_retry_strategy(DefaultRetryStrategy::new("123"))
+            .hosts(vec![Host::new("localhost")])
+            .build();
+        assert!(client.is_err())
+    }
+
+    #[test]
+    fn test_client_builder_requires_requester_without_reqwest() {
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .build();
+        assert_eq!(client.is_ok(), cfg!(feature = "reqwest"))
+    }
+
+    #[tokio::test]
+    async fn test_client_uses_http_requester() {
+        let requester = InMemoryRequester::new();
+        requester.push_response(HttpResponse::new(
+            http::StatusCode::OK,
+            r#"{"items":[{"name":"Games","entries":3}]}"#,
+        ));
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("APP")
+            .add_header("X-Request-Source", "ci")
+            .set_http_requester(requester.clone())
+            .build()
+            .unwrap();
+        let indexes = client.list_indexes_async().await.unwrap();
+        assert_eq!(indexes[0].name, "Games");
+        let requests = requester.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://APP-dsn.algolia.net/1/indexes");
+        assert_eq!(requests[0].headers["x-algolia-application-id"], "APP");
+        assert_eq!(requests[0].headers["x-request-source"], "ci");
+        assert_eq!(requests[0].headers["user-agent"], AGENT);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_client_uses_custom_host() {
+        use std::{
+            io::{
+                BufRead,
+                BufReader,
+                Write,
+            },
+            net::TcpListener,
+        };
+        use EasyAlgolia::retry::{
+            Host,
+            Scheme,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            assert_eq!(request_line.trim_end(), "GET /1/indexes HTTP/1.1");
+            // the request has no body, the headers end with an empty line
+            let mut headers = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                headers.push(line.trim_end().to_ascii_lowercase());
+                line.clear();
+            }
+            let agent = format!("{}; myapp (1.2.0)", AGENT.to_ascii_lowercase());
+            assert!(headers.contains(&format!("x-algolia-agent: {agent}")));
+            assert!(headers.contains(&format!("user-agent: {agent}")));
+            assert!(headers.contains(&"x-request-source: ci".to_string()));
+            let body = r#"{"items":[{"name":"Games","entries":3}]}"#;
+            write!(
+                reader.into_inner(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+        let client = ClientBuilder::new()
+            .set_api_key("123")
+            .set_application_id("123")
+            .hosts(vec![Host::new("127.0.0.1").scheme(Scheme::Http).port(port)])
+            .add_agent_segment("MyApp (1.2.0)")
+            .add_header("X-Request-Source", "ci")
+            .build()
+            .unwrap();
+        let indexes = client.list_indexes_async().await.unwrap();
+        assert_eq!(indexes[0].name, "Games");
+        assert_eq!(indexes[0].entries, 3);
+    }
 }